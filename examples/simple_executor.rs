@@ -54,7 +54,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     colonyname: colonyname.to_string(),
                     executorname: "rust-executor".to_string(),
                     message: format!("Processing function: {}", process.spec.funcname),
-                    timestamp: 0,
+                    timestamp: "0".to_string(),
                 };
                 let _ = colonies::add_log(&log, executor_prvkey).await;
 
@@ -4,7 +4,7 @@
 //!
 //! Run with: cargo run --example submit_process
 
-use colonyos::core::{FunctionSpec, SUCCESS, FAILED, WAITING, RUNNING};
+use colonyos::core::{FunctionSpec, ProcessState};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -32,7 +32,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Wait for completion
     println!("Waiting for completion...");
-    let mut last_state = WAITING;
+    let mut last_state = ProcessState::Waiting;
 
     loop {
         let p = colonyos::get_process(&process.processid, prvkey).await?;
@@ -40,17 +40,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Print state changes
         if p.state != last_state {
             match p.state {
-                WAITING => println!("State: WAITING"),
-                RUNNING => println!("State: RUNNING (assigned to executor)"),
-                SUCCESS => println!("State: SUCCESS"),
-                FAILED => println!("State: FAILED"),
-                _ => println!("State: {}", p.state),
+                ProcessState::Waiting => println!("State: WAITING"),
+                ProcessState::Running => println!("State: RUNNING (assigned to executor)"),
+                ProcessState::Success => println!("State: SUCCESS"),
+                ProcessState::Failed => println!("State: FAILED"),
+                ProcessState::Unknown(v) => println!("State: {}", v),
             }
             last_state = p.state;
         }
 
         match p.state {
-            SUCCESS => {
+            ProcessState::Success => {
                 println!("\n=== Process Completed Successfully ===");
                 println!("Output: {:?}", p.output);
                 if !p.attributes.is_empty() {
@@ -61,7 +61,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 }
                 break;
             }
-            FAILED => {
+            ProcessState::Failed => {
                 println!("\n=== Process Failed ===");
                 println!("Errors: {:?}", p.errors);
                 break;
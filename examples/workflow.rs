@@ -5,7 +5,7 @@
 //!
 //! Run with: cargo run --example workflow
 
-use colonies::core::{FunctionSpec, WorkflowSpec, SUCCESS, FAILED};
+use colonies::core::{FunctionSpec, WorkflowSpec, ProcessState};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -57,7 +57,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let pg = colonies::get_processgraph(&processgraph.processgraphid, prvkey).await?;
 
         match pg.state {
-            s if s == SUCCESS => {
+            ProcessState::Success => {
                 println!("=== Workflow Completed Successfully ===");
 
                 // Get details of each process
@@ -66,19 +66,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     println!(
                         "  {} ({}): {:?}",
                         p.spec.nodename,
-                        if p.state == SUCCESS { "SUCCESS" } else { "FAILED" },
+                        if p.state == ProcessState::Success { "SUCCESS" } else { "FAILED" },
                         p.output
                     );
                 }
                 break;
             }
-            s if s == FAILED => {
+            ProcessState::Failed => {
                 println!("=== Workflow Failed ===");
 
                 // Show which processes failed
                 for pid in &pg.processids {
                     let p = colonies::get_process(pid, prvkey).await?;
-                    if p.state == FAILED {
+                    if p.state == ProcessState::Failed {
                         println!("  {} failed: {:?}", p.spec.nodename, p.errors);
                     }
                 }
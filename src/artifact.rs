@@ -0,0 +1,125 @@
+//! File artifacts attached to a process.
+//!
+//! `colonyos::add_attr` only carries small string key/value pairs, but many
+//! workloads produce files: build output, logs, result blobs. This module
+//! streams a file to/from the server's object store keyed by process id, so
+//! an executor can attach build output to a process the same way a CI
+//! runner attaches compiled binaries, without buffering the whole file in
+//! memory.
+
+use crate::rpc::{get_server_url, http_client, RPCError};
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Sha3_256};
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+/// Metadata the server reports back once an artifact is stored.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ArtifactMeta {
+    pub processid: String,
+    pub name: String,
+    pub checksum: String,
+    pub size: u64,
+}
+
+fn artifact_url(processid: &str, name: &str) -> String {
+    let base = get_server_url().replace("/api", "/artifacts");
+    format!("{base}/{processid}/{name}")
+}
+
+/// Streams `reader` to the object store under `processid`/`name`, without
+/// buffering the whole file in memory. Returns the server-reported size and
+/// checksum once the upload completes.
+pub async fn upload_artifact<R>(
+    processid: &str,
+    name: &str,
+    reader: R,
+    prvkey: &str,
+) -> Result<ArtifactMeta, RPCError>
+where
+    R: AsyncRead + Send + Sync + Unpin + 'static,
+{
+    let signature = crate::crypto::gen_signature(processid, prvkey);
+    let body = reqwest::Body::wrap_stream(ReaderStream::new(reader));
+
+    let client = http_client();
+    let res = client
+        .put(artifact_url(processid, name))
+        .header("x-colonies-signature", signature)
+        .body(body)
+        .send()
+        .await
+        .map_err(|e| RPCError::new(&e.to_string(), true))?;
+
+    if !res.status().is_success() {
+        return Err(RPCError::new(
+            &format!("upload_artifact failed with status {}", res.status()),
+            false,
+        ));
+    }
+
+    res.json::<ArtifactMeta>()
+        .await
+        .map_err(|e| RPCError::new(&e.to_string(), false))
+}
+
+/// Streams the artifact stored under `processid`/`name` from the object
+/// store, yielding chunks as they arrive instead of buffering the whole
+/// file in memory.
+pub async fn download_artifact(
+    processid: &str,
+    name: &str,
+    prvkey: &str,
+) -> Result<impl Stream<Item = Result<bytes::Bytes, RPCError>>, RPCError> {
+    let signature = crate::crypto::gen_signature(processid, prvkey);
+
+    let client = http_client();
+    let res = client
+        .get(artifact_url(processid, name))
+        .header("x-colonies-signature", signature)
+        .send()
+        .await
+        .map_err(|e| RPCError::new(&e.to_string(), true))?;
+
+    if !res.status().is_success() {
+        return Err(RPCError::new(
+            &format!("download_artifact failed with status {}", res.status()),
+            false,
+        ));
+    }
+
+    use futures_util::StreamExt;
+    Ok(res.bytes_stream().map(|r| r.map_err(|e| RPCError::new(&e.to_string(), true))))
+}
+
+/// Computes the SHA3-256 checksum of `bytes`, hex-encoded. Useful for
+/// callers that want to verify a downloaded artifact against the
+/// server-reported checksum.
+pub fn checksum(bytes: &[u8]) -> String {
+    let mut hasher = Sha3_256::new();
+    hasher.update(bytes);
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artifact_url() {
+        crate::set_server_url("http://localhost:50080/api");
+        assert_eq!(
+            artifact_url("p1", "build.tar.gz"),
+            "http://localhost:50080/artifacts/p1/build.tar.gz"
+        );
+    }
+
+    #[test]
+    fn test_checksum_matches_known_vector() {
+        assert_eq!(
+            checksum(b"hello"),
+            "3338be694f50c5f338814986cdca92c14ec00359021af6c79dcb32e4f258edb"
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Exponential backoff with full jitter for retrying after connection
+//! failures.
+//!
+//! Used by [`crate::executor`] to avoid hammering a recovering server: a
+//! hard-coded sleep between retries either wastes time (too long) or piles
+//! on load right as the server comes back (too short, no jitter). Full
+//! jitter spreads retries out while still backing off exponentially.
+
+use rand::Rng;
+use std::sync::Mutex;
+use std::time::Duration;
+
+static DEFAULT_POLICY: Mutex<Option<BackoffPolicy>> = Mutex::new(None);
+
+/// Sets the client-wide default [`BackoffPolicy`], consulted by
+/// [`crate::assign_resilient`], [`crate::channel_append_resilient`],
+/// [`crate::channel_read_resilient`], and every builder in this SDK
+/// (`ExecutorRuntime::backoff`, `ReconcilerWorker::backoff`,
+/// `ReconnectConfig::backoff`) that isn't given an explicit policy of its
+/// own. Takes effect on the next retryable call.
+pub fn set_default_policy(policy: BackoffPolicy) {
+    *DEFAULT_POLICY.lock().unwrap() = Some(policy);
+}
+
+/// Returns the configured default policy, or [`BackoffPolicy::default`] if
+/// [`set_default_policy`] was never called.
+pub fn default_policy() -> BackoffPolicy {
+    DEFAULT_POLICY.lock().unwrap().unwrap_or_default()
+}
+
+/// How [`BackoffPolicy::delay`] randomizes the exponential delay before
+/// returning it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JitterStrategy {
+    /// Uniformly sampled from `[0, unjittered]` ("full jitter"). Spreads
+    /// retries out the most, at the cost of some retries firing almost
+    /// immediately.
+    Full,
+    /// Uniformly sampled from `unjittered * [1 - factor, 1 + factor]`, e.g.
+    /// `Proportional(0.2)` for ±20%. Keeps delays close to the exponential
+    /// curve while still avoiding a reconnect stampede across a fleet of
+    /// executors hitting the same failure at the same time.
+    Proportional(f64),
+}
+
+/// Backoff configuration consulted on each connection failure.
+///
+/// `max_retries = None` means retry forever.
+#[derive(Debug, Clone, Copy)]
+pub struct BackoffPolicy {
+    base: Duration,
+    cap: Duration,
+    max_retries: Option<u32>,
+    jitter: JitterStrategy,
+}
+
+impl Default for BackoffPolicy {
+    fn default() -> BackoffPolicy {
+        BackoffPolicy {
+            base: Duration::from_millis(200),
+            cap: Duration::from_secs(30),
+            max_retries: None,
+            jitter: JitterStrategy::Full,
+        }
+    }
+}
+
+impl BackoffPolicy {
+    pub fn new() -> BackoffPolicy {
+        BackoffPolicy::default()
+    }
+
+    /// Sets the initial delay (the delay after the first failure).
+    pub fn base(mut self, base: Duration) -> BackoffPolicy {
+        self.base = base;
+        self
+    }
+
+    /// Sets the maximum delay, regardless of how many attempts have failed.
+    pub fn cap(mut self, cap: Duration) -> BackoffPolicy {
+        self.cap = cap;
+        self
+    }
+
+    /// Sets how many consecutive failures are tolerated before giving up.
+    /// `None` (the default) retries forever.
+    pub fn max_retries(mut self, max_retries: u32) -> BackoffPolicy {
+        self.max_retries = Some(max_retries);
+        self
+    }
+
+    /// Sets how `delay` randomizes the exponential delay. Defaults to
+    /// [`JitterStrategy::Full`].
+    pub fn jitter(mut self, jitter: JitterStrategy) -> BackoffPolicy {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Returns `false` once `attempt` (a 0-based consecutive-failure count)
+    /// has exhausted `max_retries`.
+    pub fn should_retry(&self, attempt: u32) -> bool {
+        match self.max_retries {
+            Some(max) => attempt < max,
+            None => true,
+        }
+    }
+
+    /// Computes the delay before the `attempt`'th retry (0-based):
+    /// `base * 2^attempt` capped at `cap`, then randomized according to
+    /// `jitter`.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let shift = attempt.min(31);
+        let unjittered = self
+            .base
+            .checked_mul(1u32.checked_shl(shift).unwrap_or(u32::MAX))
+            .unwrap_or(self.cap)
+            .min(self.cap);
+
+        if unjittered.is_zero() {
+            return unjittered;
+        }
+
+        match self.jitter {
+            JitterStrategy::Full => rand::thread_rng().gen_range(Duration::ZERO..=unjittered),
+            JitterStrategy::Proportional(factor) => {
+                let factor = factor.clamp(0.0, 1.0);
+                let low = unjittered.mul_f64(1.0 - factor);
+                let high = unjittered.mul_f64(1.0 + factor);
+                rand::thread_rng().gen_range(low..=high)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_defaults() {
+        let policy = BackoffPolicy::new();
+        assert_eq!(policy.base, Duration::from_millis(200));
+        assert_eq!(policy.cap, Duration::from_secs(30));
+        assert!(policy.max_retries.is_none());
+    }
+
+    #[test]
+    fn test_should_retry_forever_by_default() {
+        let policy = BackoffPolicy::new();
+        assert!(policy.should_retry(1_000_000));
+    }
+
+    #[test]
+    fn test_should_retry_respects_max_retries() {
+        let policy = BackoffPolicy::new().max_retries(3);
+        assert!(policy.should_retry(0));
+        assert!(policy.should_retry(2));
+        assert!(!policy.should_retry(3));
+    }
+
+    #[test]
+    fn test_delay_capped() {
+        let policy = BackoffPolicy::new().base(Duration::from_secs(1)).cap(Duration::from_secs(5));
+        for attempt in 0..40 {
+            assert!(policy.delay(attempt) <= Duration::from_secs(5));
+        }
+    }
+
+    #[test]
+    fn test_delay_grows_with_attempt_before_cap() {
+        let policy = BackoffPolicy::new().base(Duration::from_millis(100)).cap(Duration::from_secs(60));
+        for _ in 0..50 {
+            assert!(policy.delay(0) <= Duration::from_millis(100));
+            assert!(policy.delay(3) <= Duration::from_millis(800));
+        }
+    }
+
+    #[test]
+    fn test_proportional_jitter_stays_within_factor() {
+        let policy = BackoffPolicy::new()
+            .base(Duration::from_millis(100))
+            .cap(Duration::from_secs(30))
+            .jitter(JitterStrategy::Proportional(0.2));
+        for _ in 0..50 {
+            let delay = policy.delay(2); // unjittered = 400ms
+            assert!(delay >= Duration::from_millis(320));
+            assert!(delay <= Duration::from_millis(480));
+        }
+    }
+
+    #[test]
+    fn test_proportional_jitter_respects_cap() {
+        let policy = BackoffPolicy::new()
+            .base(Duration::from_secs(1))
+            .cap(Duration::from_secs(5))
+            .jitter(JitterStrategy::Proportional(0.2));
+        for attempt in 0..40 {
+            assert!(policy.delay(attempt) <= Duration::from_secs(6));
+        }
+    }
+
+    #[test]
+    fn test_default_jitter_strategy_is_full() {
+        let policy = BackoffPolicy::new();
+        assert_eq!(policy.jitter, JitterStrategy::Full);
+    }
+
+    #[test]
+    fn test_default_policy_falls_back_when_unset() {
+        // Other tests in this process may call `set_default_policy`, so
+        // only assert the fallback shape rather than relying on global
+        // ordering: `max_retries` is the field most tests configure.
+        let policy = default_policy();
+        assert_eq!(policy.base, BackoffPolicy::default().base);
+    }
+
+    #[test]
+    fn test_set_default_policy_is_observed() {
+        set_default_policy(BackoffPolicy::new().max_retries(9));
+        assert_eq!(default_policy().max_retries, Some(9));
+    }
+}
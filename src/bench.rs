@@ -0,0 +1,323 @@
+//! Workload-driven benchmarking harness for submit/assign throughput.
+//!
+//! Reads a JSON workload file describing a batch of `FunctionSpec`s, then
+//! drives `submit` and polls `get_process` until every process completes,
+//! recording per-process queue-wait and execution latencies. This gives
+//! maintainers a repeatable way to measure server/SDK performance
+//! regressions instead of eyeballing the polling examples. [`ping`] is a
+//! lighter-weight sibling modeled on sequential "ping" tooling: instead of
+//! polling, it drives the `submit`/`assign`/`close` round-trip directly
+//! with an executor key in hand, so a single call gives a quick
+//! assignment/close latency health check without writing a workload file.
+
+use crate::core::{is_epoch_colony_date, FunctionSpec, ProcessState};
+use crate::rpc::{http_client, RPCError};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// A workload file: a template spec repeated `count` times, plus how many
+/// submissions may be outstanding at once.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Workload {
+    pub spec: FunctionSpec,
+    pub count: u32,
+    #[serde(default = "default_concurrency")]
+    pub concurrency: u32,
+    #[serde(default)]
+    pub warmup: u32,
+    #[serde(default)]
+    pub poll_interval_ms: u64,
+}
+
+fn default_concurrency() -> u32 {
+    1
+}
+
+/// Per-process latency sample collected while driving a workload.
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    queue_wait: Duration,
+    exec_time: Duration,
+    success: bool,
+}
+
+/// Aggregate result of a benchmark run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BenchReport {
+    pub submitted: u32,
+    pub succeeded: u32,
+    pub failed: u32,
+    /// Attempts that failed with `RPCError::conn_err()` set, i.e. the
+    /// transport itself broke, counted separately from `failed` so a flaky
+    /// network can be told apart from an overloaded scheduler rejecting
+    /// requests cleanly. Always 0 for [`run`], which only measures
+    /// already-submitted processes via polling.
+    pub connection_errors: u32,
+    pub throughput_per_sec: f64,
+    pub queue_wait_p50_ms: f64,
+    pub queue_wait_p95_ms: f64,
+    pub queue_wait_p99_ms: f64,
+    pub exec_time_p50_ms: f64,
+    pub exec_time_p95_ms: f64,
+    pub exec_time_p99_ms: f64,
+}
+
+fn percentile(mut values: Vec<f64>, pct: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let idx = ((values.len() as f64 - 1.0) * pct).round() as usize;
+    values[idx]
+}
+
+fn summarize(samples: &[Sample], elapsed: Duration) -> BenchReport {
+    let succeeded = samples.iter().filter(|s| s.success).count() as u32;
+    let failed = samples.len() as u32 - succeeded;
+    let queue_waits: Vec<f64> = samples.iter().map(|s| s.queue_wait.as_secs_f64() * 1000.0).collect();
+    let exec_times: Vec<f64> = samples.iter().map(|s| s.exec_time.as_secs_f64() * 1000.0).collect();
+
+    BenchReport {
+        submitted: samples.len() as u32,
+        succeeded,
+        failed,
+        connection_errors: 0,
+        throughput_per_sec: if elapsed.as_secs_f64() > 0.0 {
+            samples.len() as f64 / elapsed.as_secs_f64()
+        } else {
+            0.0
+        },
+        queue_wait_p50_ms: percentile(queue_waits.clone(), 0.50),
+        queue_wait_p95_ms: percentile(queue_waits.clone(), 0.95),
+        queue_wait_p99_ms: percentile(queue_waits, 0.99),
+        exec_time_p50_ms: percentile(exec_times.clone(), 0.50),
+        exec_time_p95_ms: percentile(exec_times.clone(), 0.95),
+        exec_time_p99_ms: percentile(exec_times, 0.99),
+    }
+}
+
+/// Parses a workload description from a JSON string.
+pub fn parse_workload(json: &str) -> Result<Workload, serde_json::Error> {
+    serde_json::from_str(json)
+}
+
+/// Submits `workload.count` processes (after `workload.warmup` untimed
+/// submissions) with up to `workload.concurrency` outstanding at once, and
+/// polls each to completion, returning the aggregate report.
+pub async fn run(workload: &Workload, prvkey: &str) -> Result<BenchReport, RPCError> {
+    for _ in 0..workload.warmup {
+        let _ = crate::submit(&workload.spec, prvkey).await;
+    }
+
+    let started = Instant::now();
+    let mut samples = Vec::with_capacity(workload.count as usize);
+    let mut in_flight: Vec<(String, Instant)> = Vec::new();
+
+    for i in 0..workload.count {
+        if in_flight.len() as u32 >= workload.concurrency.max(1) {
+            drain_one(&mut in_flight, &mut samples, prvkey, workload.poll_interval_ms).await;
+        }
+        let submit_start = Instant::now();
+        let process = crate::submit(&workload.spec, prvkey).await?;
+        let _ = i;
+        in_flight.push((process.processid, submit_start));
+    }
+
+    while !in_flight.is_empty() {
+        drain_one(&mut in_flight, &mut samples, prvkey, workload.poll_interval_ms).await;
+    }
+
+    Ok(summarize(&samples, started.elapsed()))
+}
+
+async fn drain_one(
+    in_flight: &mut Vec<(String, Instant)>,
+    samples: &mut Vec<Sample>,
+    prvkey: &str,
+    poll_interval_ms: u64,
+) {
+    let interval = Duration::from_millis(poll_interval_ms.max(50));
+    loop {
+        let mut i = 0;
+        while i < in_flight.len() {
+            let (processid, submit_start) = &in_flight[i];
+            if let Ok(process) = crate::get_process(processid, prvkey).await {
+                if process.state == ProcessState::Success || process.state == ProcessState::Failed {
+                    let queue_wait = is_epoch_colony_date(&process.starttime)
+                        .then(Duration::default)
+                        .unwrap_or_default();
+                    samples.push(Sample {
+                        queue_wait,
+                        exec_time: submit_start.elapsed(),
+                        success: process.state == ProcessState::Success,
+                    });
+                    in_flight.remove(i);
+                    return;
+                }
+            }
+            i += 1;
+        }
+        tokio::time::sleep(interval).await;
+    }
+}
+
+/// Submits a trivial process `count` times, one after another, timing how
+/// long `assign` takes to hand each one to `executor_key` and how long the
+/// follow-up `close` round-trip takes. Equivalent to `ping_concurrent` with
+/// a concurrency of 1.
+pub async fn ping(colonyname: &str, count: u32, executor_key: &str, submitter_key: &str) -> BenchReport {
+    ping_concurrent(colonyname, count, 1, executor_key, submitter_key).await
+}
+
+/// Like [`ping`], but keeps up to `concurrency` submit/assign/close
+/// round-trips in flight at once, to measure throughput under load instead
+/// of pure one-at-a-time latency.
+pub async fn ping_concurrent(
+    colonyname: &str,
+    count: u32,
+    concurrency: u32,
+    executor_key: &str,
+    submitter_key: &str,
+) -> BenchReport {
+    let started = Instant::now();
+    let samples = Arc::new(Mutex::new(Vec::with_capacity(count as usize)));
+    let connection_errors = Arc::new(AtomicU32::new(0));
+    let remaining = Arc::new(AtomicU32::new(count));
+
+    let mut workers = Vec::new();
+    for _ in 0..concurrency.max(1) {
+        let colonyname = colonyname.to_owned();
+        let executor_key = executor_key.to_owned();
+        let submitter_key = submitter_key.to_owned();
+        let samples = samples.clone();
+        let connection_errors = connection_errors.clone();
+        let remaining = remaining.clone();
+
+        workers.push(tokio::spawn(async move {
+            loop {
+                let claimed = remaining
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |r| r.checked_sub(1))
+                    .is_ok();
+                if !claimed {
+                    break;
+                }
+                match ping_once(&colonyname, &executor_key, &submitter_key).await {
+                    Ok(sample) => samples.lock().await.push(sample),
+                    Err(e) if e.conn_err() => {
+                        connection_errors.fetch_add(1, Ordering::SeqCst);
+                    }
+                    Err(_) => {
+                        samples.lock().await.push(Sample {
+                            queue_wait: Duration::ZERO,
+                            exec_time: Duration::ZERO,
+                            success: false,
+                        });
+                    }
+                }
+            }
+        }));
+    }
+    for worker in workers {
+        let _ = worker.await;
+    }
+
+    let samples = Arc::try_unwrap(samples)
+        .expect("all workers joined, no other Arc handles remain")
+        .into_inner();
+    let mut report = summarize(&samples, started.elapsed());
+    report.connection_errors = connection_errors.load(Ordering::SeqCst);
+    report
+}
+
+/// One submit -> assign -> close round-trip: `queue_wait` is the time from
+/// submit until `assign` returns the process, `exec_time` is the time
+/// `close` takes once assigned.
+async fn ping_once(colonyname: &str, executor_key: &str, submitter_key: &str) -> Result<Sample, RPCError> {
+    let spec = FunctionSpec::new("ping", "", colonyname);
+
+    let submit_start = Instant::now();
+    crate::submit(&spec, submitter_key).await?;
+    let process = crate::assign(colonyname, 10, executor_key).await?;
+    let queue_wait = submit_start.elapsed();
+
+    let close_start = Instant::now();
+    crate::close(&process.processid, executor_key).await?;
+    let exec_time = close_start.elapsed();
+
+    Ok(Sample {
+        queue_wait,
+        exec_time,
+        success: true,
+    })
+}
+
+/// POSTs a report to a configurable results endpoint for trend tracking
+/// across commits.
+pub async fn publish_report(endpoint: &str, report: &BenchReport) -> Result<(), RPCError> {
+    let client = http_client();
+    client
+        .post(endpoint)
+        .json(report)
+        .send()
+        .await
+        .map_err(|e| RPCError::new(&e.to_string(), true))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_workload() {
+        let json = r#"{
+            "spec": {"funcname": "echo", "conditions": {"colonyname": "c", "executortype": "cli"}},
+            "count": 10,
+            "concurrency": 4
+        }"#;
+        let workload = parse_workload(json).unwrap();
+        assert_eq!(workload.count, 10);
+        assert_eq!(workload.concurrency, 4);
+        assert_eq!(workload.warmup, 0);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(percentile(values.clone(), 0.0), 1.0);
+        assert_eq!(percentile(values, 1.0), 5.0);
+    }
+
+    #[test]
+    fn test_percentile_empty() {
+        assert_eq!(percentile(vec![], 0.5), 0.0);
+    }
+
+    #[test]
+    fn test_summarize_counts() {
+        let samples = vec![
+            Sample { queue_wait: Duration::from_millis(10), exec_time: Duration::from_millis(20), success: true },
+            Sample { queue_wait: Duration::from_millis(30), exec_time: Duration::from_millis(40), success: false },
+        ];
+        let report = summarize(&samples, Duration::from_secs(1));
+        assert_eq!(report.submitted, 2);
+        assert_eq!(report.succeeded, 1);
+        assert_eq!(report.failed, 1);
+    }
+
+    #[test]
+    fn test_summarize_leaves_connection_errors_for_caller_to_fill_in() {
+        let report = summarize(&[], Duration::from_secs(1));
+        assert_eq!(report.connection_errors, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ping_concurrent_zero_count_returns_empty_report() {
+        let report = ping_concurrent("mycolony", 0, 4, "executor_key", "submitter_key").await;
+        assert_eq!(report.submitted, 0);
+        assert_eq!(report.connection_errors, 0);
+    }
+}
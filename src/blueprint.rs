@@ -0,0 +1,268 @@
+//! Schema-enforced Blueprint instances.
+//!
+//! `BlueprintDefinition.spec.schema` holds a JSON Schema, but nothing
+//! enforces it against the free-form `Blueprint.spec` map. This compiles
+//! the stored schema once and validates a blueprint's `spec` against it,
+//! turning the CRD-style definition/instance pair into an enforced
+//! contract so malformed replica counts or missing images are rejected
+//! before a reconciler ever sees them.
+//!
+//! [`blueprint_definition_conflict`] and [`blueprint_conflict`] cover the
+//! other half of that contract: whether a name is even safe to (re)claim in
+//! the first place, so two incompatible registrations under the same name
+//! don't silently clobber one another.
+
+use crate::core::{Blueprint, BlueprintDefinition};
+use jsonschema::JSONSchema;
+
+/// A single schema-validation failure.
+#[derive(Debug, Clone)]
+pub struct ValidationError {
+    pub instance_path: String,
+    pub schema_keyword: String,
+    pub message: String,
+}
+
+impl BlueprintDefinition {
+    /// Validates `bp.spec` against this definition's stored JSON Schema.
+    /// Returns `Ok(())` when no schema is set on the definition, since
+    /// there's nothing to enforce.
+    pub fn validate(&self, bp: &Blueprint) -> Result<(), Vec<ValidationError>> {
+        let Some(schema) = &self.spec.schema else {
+            return Ok(());
+        };
+
+        let compiled = JSONSchema::compile(schema).map_err(|e| {
+            vec![ValidationError {
+                instance_path: String::new(),
+                schema_keyword: "schema".to_owned(),
+                message: format!("invalid schema: {e}"),
+            }]
+        })?;
+
+        let instance = serde_json::to_value(&bp.spec).unwrap_or(serde_json::Value::Null);
+
+        compiled.validate(&instance).map_err(|errors| {
+            errors
+                .map(|e| ValidationError {
+                    instance_path: e.instance_path.to_string(),
+                    schema_keyword: e.kind.to_string(),
+                    message: e.to_string(),
+                })
+                .collect()
+        })
+    }
+}
+
+/// A name that's already registered under an incompatible mapping.
+///
+/// Returned instead of silently overwriting, so a second `thermostat-def`
+/// registration with a different schema is rejected rather than clobbering
+/// the first one out from under whatever already depends on it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conflict {
+    pub name: String,
+    pub differing_fields: Vec<String>,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "conflicting registration for '{}': differs in {}",
+            self.name,
+            self.differing_fields.join(", ")
+        )
+    }
+}
+
+/// Compares an incoming `BlueprintDefinition` registration against the one
+/// already on record under the same name, returning a [`Conflict`] naming
+/// the fields that differ. `None` means either there's nothing registered
+/// yet or `incoming` matches `existing`, so the registration is a no-op.
+pub fn blueprint_definition_conflict(
+    existing: &BlueprintDefinition,
+    incoming: &BlueprintDefinition,
+) -> Option<Conflict> {
+    let mut differing_fields = Vec::new();
+    if existing.kind != incoming.kind {
+        differing_fields.push("kind".to_owned());
+    }
+    if existing.spec.names.kind != incoming.spec.names.kind
+        || existing.spec.names.singular != incoming.spec.names.singular
+        || existing.spec.names.plural != incoming.spec.names.plural
+    {
+        differing_fields.push("spec.names".to_owned());
+    }
+    if existing.spec.handler.executor_type != incoming.spec.handler.executor_type {
+        differing_fields.push("spec.handler".to_owned());
+    }
+    if existing.spec.schema != incoming.spec.schema {
+        differing_fields.push("spec.schema".to_owned());
+    }
+
+    if differing_fields.is_empty() {
+        None
+    } else {
+        Some(Conflict {
+            name: incoming.metadata.name.clone(),
+            differing_fields,
+        })
+    }
+}
+
+/// Compares an incoming `Blueprint` registration against the one already on
+/// record under the same name. Unlike [`blueprint_definition_conflict`],
+/// this only looks at `kind` and `handler`: `spec`/`status` are expected to
+/// change across updates, so a differing `spec` is a routine edit, not a
+/// name collision between two unrelated blueprints.
+pub fn blueprint_conflict(existing: &Blueprint, incoming: &Blueprint) -> Option<Conflict> {
+    let mut differing_fields = Vec::new();
+    if existing.kind != incoming.kind {
+        differing_fields.push("kind".to_owned());
+    }
+    if existing.handler.executortype != incoming.handler.executortype {
+        differing_fields.push("handler".to_owned());
+    }
+
+    if differing_fields.is_empty() {
+        None
+    } else {
+        Some(Conflict {
+            name: incoming.metadata.name.clone(),
+            differing_fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{BlueprintDefinitionSpec, BlueprintMetadata};
+    use serde_json::json;
+
+    fn definition_with_schema(schema: serde_json::Value) -> BlueprintDefinition {
+        BlueprintDefinition {
+            kind: "Deployment".to_owned(),
+            metadata: BlueprintMetadata {
+                name: "deployment-def".to_owned(),
+                colonyname: "production".to_owned(),
+            },
+            spec: BlueprintDefinitionSpec {
+                schema: Some(schema),
+                ..BlueprintDefinitionSpec::default()
+            },
+        }
+    }
+
+    fn blueprint_with_spec(spec: serde_json::Value) -> Blueprint {
+        Blueprint {
+            spec: serde_json::from_value(spec).unwrap(),
+            ..Blueprint::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_passes_for_matching_spec() {
+        let def = definition_with_schema(json!({
+            "type": "object",
+            "required": ["replicas", "image"],
+            "properties": {
+                "replicas": {"type": "number"},
+                "image": {"type": "string"}
+            }
+        }));
+        let bp = blueprint_with_spec(json!({"replicas": 3, "image": "myapp:latest"}));
+
+        assert!(def.validate(&bp).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_missing_required_field() {
+        let def = definition_with_schema(json!({
+            "type": "object",
+            "required": ["replicas", "image"],
+            "properties": {
+                "replicas": {"type": "number"},
+                "image": {"type": "string"}
+            }
+        }));
+        let bp = blueprint_with_spec(json!({"replicas": 3}));
+
+        let errors = def.validate(&bp).unwrap_err();
+        assert!(!errors.is_empty());
+    }
+
+    #[test]
+    fn test_validate_rejects_wrong_type() {
+        let def = definition_with_schema(json!({
+            "type": "object",
+            "properties": {
+                "replicas": {"type": "number"}
+            }
+        }));
+        let bp = blueprint_with_spec(json!({"replicas": "three"}));
+
+        let errors = def.validate(&bp).unwrap_err();
+        assert!(errors.iter().any(|e| e.instance_path.contains("replicas")));
+    }
+
+    #[test]
+    fn test_validate_passes_when_no_schema_set() {
+        let def = BlueprintDefinition::default();
+        let bp = blueprint_with_spec(json!({"anything": "goes"}));
+
+        assert!(def.validate(&bp).is_ok());
+    }
+
+    #[test]
+    fn test_blueprint_definition_conflict_none_when_identical() {
+        let existing = definition_with_schema(json!({"type": "object"}));
+        let incoming = existing.clone();
+
+        assert!(blueprint_definition_conflict(&existing, &incoming).is_none());
+    }
+
+    #[test]
+    fn test_blueprint_definition_conflict_detects_differing_schema() {
+        let existing = definition_with_schema(json!({"type": "object"}));
+        let incoming = definition_with_schema(json!({"type": "string"}));
+
+        let conflict = blueprint_definition_conflict(&existing, &incoming).unwrap();
+        assert_eq!(conflict.name, "deployment-def");
+        assert!(conflict.differing_fields.contains(&"spec.schema".to_owned()));
+    }
+
+    #[test]
+    fn test_blueprint_definition_conflict_detects_differing_kind() {
+        let existing = definition_with_schema(json!({"type": "object"}));
+        let mut incoming = existing.clone();
+        incoming.kind = "StatefulSet".to_owned();
+
+        let conflict = blueprint_definition_conflict(&existing, &incoming).unwrap();
+        assert!(conflict.differing_fields.contains(&"kind".to_owned()));
+    }
+
+    #[test]
+    fn test_blueprint_conflict_none_for_routine_spec_update() {
+        let existing = blueprint_with_spec(json!({"replicas": 1}));
+        let incoming = blueprint_with_spec(json!({"replicas": 5}));
+
+        assert!(blueprint_conflict(&existing, &incoming).is_none());
+    }
+
+    #[test]
+    fn test_blueprint_conflict_detects_differing_kind() {
+        let existing = Blueprint {
+            kind: "Deployment".to_owned(),
+            ..blueprint_with_spec(json!({"replicas": 1}))
+        };
+        let incoming = Blueprint {
+            kind: "Thermostat".to_owned(),
+            ..blueprint_with_spec(json!({"replicas": 1}))
+        };
+
+        let conflict = blueprint_conflict(&existing, &incoming).unwrap();
+        assert!(conflict.differing_fields.contains(&"kind".to_owned()));
+    }
+}
@@ -0,0 +1,221 @@
+//! Auto-detection of `Hardware` capabilities from the local container
+//! runtime.
+//!
+//! Operators currently hand-write the `Capabilities` JSON exercised in
+//! `test_capabilities_with_hardware_and_software`. This module queries
+//! `docker system info` (falling back to `podman system info` when Docker
+//! isn't installed) and maps the result onto `Hardware`, so an executor can
+//! self-report accurate capabilities on startup instead.
+
+use crate::core::{Capabilities, Hardware, Software};
+use serde_json::Value;
+use std::path::Path;
+use std::process::Command;
+
+const DEFAULT_PCI_ROOT: &str = "/sys/bus/pci/devices";
+
+/// Error returned when the container daemon can't be queried or its output
+/// can't be parsed.
+#[derive(Debug, Clone)]
+pub struct DiscoveryError {
+    pub message: String,
+}
+
+impl DiscoveryError {
+    fn new(message: impl Into<String>) -> DiscoveryError {
+        DiscoveryError {
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for DiscoveryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Discovers `Hardware` capabilities by querying the local container
+/// daemon. Pass `mock_info` with pre-captured `docker system info --format
+/// '{{json .}}'` (or podman-equivalent) output to test the mapping logic
+/// without shelling out.
+pub fn discover_hardware(mock_info: Option<&str>) -> Result<Hardware, DiscoveryError> {
+    let value = daemon_info_value(mock_info)?;
+    Ok(hardware_from_daemon_info(&value))
+}
+
+/// Discovers full `Capabilities` (hardware, GPU, and detected
+/// runtimes/drivers as `Software` entries) in one call. `pci_root` is
+/// forwarded to [`crate::gpu::discover_gpu`]; pass `None` to scan the real
+/// `/sys/bus/pci/devices`.
+pub fn discover_capabilities(
+    mock_info: Option<&str>,
+    pci_root: Option<&Path>,
+) -> Result<Capabilities, DiscoveryError> {
+    let value = daemon_info_value(mock_info)?;
+    let mut hw = hardware_from_daemon_info(&value);
+    hw.gpu = crate::gpu::discover_gpu(pci_root.unwrap_or(Path::new(DEFAULT_PCI_ROOT)));
+
+    Ok(Capabilities {
+        hardware: vec![hw],
+        software: software_from_daemon_info(&value),
+    })
+}
+
+fn daemon_info_value(mock_info: Option<&str>) -> Result<Value, DiscoveryError> {
+    let info = match mock_info {
+        Some(json) => json.to_owned(),
+        None => query_daemon_info()?,
+    };
+
+    serde_json::from_str(&info).map_err(|e| DiscoveryError::new(format!("failed to parse daemon info: {e}")))
+}
+
+fn query_daemon_info() -> Result<String, DiscoveryError> {
+    if let Ok(output) = Command::new("docker")
+        .args(["system", "info", "--format", "{{json .}}"])
+        .output()
+    {
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+    }
+
+    if let Ok(output) = Command::new("podman")
+        .args(["system", "info", "--format", "json"])
+        .output()
+    {
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+        }
+    }
+
+    Err(DiscoveryError::new(
+        "neither `docker` nor `podman` reported usable system info",
+    ))
+}
+
+fn hardware_from_daemon_info(info: &Value) -> Hardware {
+    let mut hw = Hardware {
+        nodes: 1,
+        ..Hardware::default()
+    };
+
+    if let Some(cores) = info.get("NCPU").or_else(|| info.get("Cpus")).and_then(Value::as_i64) {
+        hw.cores = cores as i32;
+    }
+
+    if let Some(mem_bytes) = info.get("MemTotal").and_then(Value::as_u64) {
+        hw.mem = format_bytes(mem_bytes);
+    }
+
+    if let Some(os_type) = info.get("OSType").and_then(Value::as_str) {
+        hw.platform = os_type.to_owned();
+    }
+
+    if let Some(arch) = info.get("Architecture").and_then(Value::as_str) {
+        hw.architecture = arch.to_owned();
+    }
+
+    hw
+}
+
+fn software_from_daemon_info(info: &Value) -> Vec<Software> {
+    let mut software = Vec::new();
+
+    if let Some(statuses) = info.get("DriverStatus").and_then(Value::as_array) {
+        for entry in statuses {
+            if let Some(pair) = entry.as_array() {
+                if pair.len() == 2 {
+                    software.push(Software {
+                        name: pair[0].as_str().unwrap_or_default().to_owned(),
+                        software_type: "driver".to_owned(),
+                        version: pair[1].as_str().unwrap_or_default().to_owned(),
+                    });
+                }
+            }
+        }
+    }
+
+    if let Some(runtimes) = info.get("Runtimes").and_then(Value::as_object) {
+        for name in runtimes.keys() {
+            software.push(Software {
+                name: name.clone(),
+                software_type: "runtime".to_owned(),
+                version: String::new(),
+            });
+        }
+    }
+
+    software
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", value, UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DOCKER_INFO: &str = r#"{
+        "NCPU": 16,
+        "MemTotal": 34359738368,
+        "OSType": "linux",
+        "Architecture": "x86_64",
+        "DriverStatus": [["Backing Filesystem", "extfs"]],
+        "Runtimes": {"runc": {}, "nvidia": {}}
+    }"#;
+
+    #[test]
+    fn test_discover_hardware_from_mock_docker_info() {
+        let hw = discover_hardware(Some(DOCKER_INFO)).unwrap();
+        assert_eq!(hw.cores, 16);
+        assert_eq!(hw.mem, "32.0GB");
+        assert_eq!(hw.platform, "linux");
+        assert_eq!(hw.architecture, "x86_64");
+        assert_eq!(hw.nodes, 1);
+    }
+
+    #[test]
+    fn test_software_from_daemon_info_includes_runtimes_and_drivers() {
+        let value: Value = serde_json::from_str(DOCKER_INFO).unwrap();
+        let software = software_from_daemon_info(&value);
+        assert!(software.iter().any(|s| s.name == "runc" && s.software_type == "runtime"));
+        assert!(software.iter().any(|s| s.name == "Backing Filesystem" && s.software_type == "driver"));
+    }
+
+    #[test]
+    fn test_discover_capabilities_from_mock_docker_info() {
+        let empty_pci_root = std::env::temp_dir().join("colonyos-capability-test-no-gpu");
+        let _ = std::fs::remove_dir_all(&empty_pci_root);
+        std::fs::create_dir_all(&empty_pci_root).unwrap();
+
+        let caps = discover_capabilities(Some(DOCKER_INFO), Some(&empty_pci_root)).unwrap();
+        assert!(!caps.is_empty());
+        assert_eq!(caps.hardware.len(), 1);
+        assert_eq!(caps.hardware[0].cores, 16);
+        assert_eq!(caps.hardware[0].gpu.count, 0);
+        assert!(caps.software.iter().any(|s| s.name == "nvidia"));
+    }
+
+    #[test]
+    fn test_discover_hardware_rejects_invalid_json() {
+        let err = discover_hardware(Some("not json")).unwrap_err();
+        assert!(err.message.contains("failed to parse"));
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512.0B");
+        assert_eq!(format_bytes(2048), "2.0KB");
+        assert_eq!(format_bytes(34359738368), "32.0GB");
+    }
+}
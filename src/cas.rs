@@ -0,0 +1,95 @@
+//! Compare-and-swap attribute updates for optimistic concurrency.
+//!
+//! Mirrors the Maelstrom key-value `cas(key, from, to, create_if_not_exists)`
+//! primitive: `compose_add_attr_rpcmsg` only supports unconditional sets,
+//! which forces races when several executors touch the same process.
+//! `cas_attribute` implements the client-side optimistic loop: re-read the
+//! process, compare the target key's current value against `expected`,
+//! submit the conditional update, and retry on conflict up to a bounded
+//! attempt count.
+
+use crate::rpc::RPCError;
+
+/// Returned by [`cas_attribute`] when the optimistic loop can't complete.
+#[derive(Debug, Clone)]
+pub enum CasError {
+    /// The underlying RPC call failed outright (not a conflict).
+    Rpc(RPCError),
+    /// Every attempt saw a value other than `expected`; the key kept
+    /// moving under us.
+    Exhausted { attempts: u32 },
+}
+
+impl std::fmt::Display for CasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CasError::Rpc(e) => write!(f, "{e}"),
+            CasError::Exhausted { attempts } => {
+                write!(f, "cas_attribute: exhausted {attempts} attempts without a matching value")
+            }
+        }
+    }
+}
+
+impl From<RPCError> for CasError {
+    fn from(e: RPCError) -> CasError {
+        CasError::Rpc(e)
+    }
+}
+
+/// Reads `processid`'s current attributes, conditionally updates `key` from
+/// `expected` to `new` (creating it if `create_if_not_exists` is set and it
+/// doesn't exist yet), and retries up to `max_attempts` times if another
+/// writer changed the value first.
+pub async fn cas_attribute(
+    processid: &str,
+    key: &str,
+    expected: &str,
+    new: &str,
+    create_if_not_exists: bool,
+    max_attempts: u32,
+    prvkey: &str,
+) -> Result<(), CasError> {
+    for _ in 0..max_attempts.max(1) {
+        let process = crate::get_process(processid, prvkey).await?;
+        let current = process.attributes.iter().find(|a| a.key == key).map(|a| a.value.as_str());
+
+        let matches_expected = match current {
+            Some(value) => value == expected,
+            None => create_if_not_exists,
+        };
+        if !matches_expected {
+            continue;
+        }
+
+        let rpcmsg = crate::rpc::compose_cas_attribute_rpcmsg(processid, key, expected, new, create_if_not_exists, prvkey);
+        match crate::rpc::send_rpcmsg(rpcmsg).await {
+            Ok(_) => return Ok(()),
+            Err(e) if e.conn_err() => return Err(CasError::Rpc(e)),
+            Err(_) => continue, // conflict reported by the server; re-read and retry
+        }
+    }
+
+    Err(CasError::Exhausted { attempts: max_attempts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cas_error_display_for_exhaustion() {
+        let err = CasError::Exhausted { attempts: 5 };
+        assert_eq!(
+            format!("{err}"),
+            "cas_attribute: exhausted 5 attempts without a matching value"
+        );
+    }
+
+    #[test]
+    fn test_cas_error_from_rpc_error() {
+        let rpc_err = RPCError::new("boom", false);
+        let err: CasError = rpc_err.into();
+        assert_eq!(format!("{err}"), "boom");
+    }
+}
@@ -0,0 +1,186 @@
+//! Hierarchical, dot-separated channel names with NATS-style wildcards.
+//!
+//! `FunctionSpec::channels` is a flat list of names, but callers often give
+//! them dotted, hierarchical structure by convention (`logs.stdout`,
+//! `logs.stderr`, `logs.worker.1`). `subscribe_channel` only understands one
+//! exact name at a time, so watching a whole namespace meant wiring up one
+//! subscription per name by hand. [`subscribe_channels`] tokenizes names on
+//! `.` and matches them against a pattern using `*` (exactly one token) and
+//! `>` (one-or-more trailing tokens, valid only as the last token) the way
+//! NATS subjects do, fans a single call out across every channel on the
+//! process that matches, and tags each delivered entry with the channel it
+//! came from so the callback can demultiplex.
+
+use crate::core::ChannelEntry;
+use crate::rpc::RPCError;
+use tokio::sync::mpsc;
+
+/// A [`ChannelEntry`] tagged with the channel it was delivered on, returned
+/// by [`subscribe_channels`] so a callback watching several channels at once
+/// can tell them apart.
+#[derive(Debug, Clone)]
+pub struct TaggedChannelEntry {
+    pub channel: String,
+    pub entry: ChannelEntry,
+}
+
+/// Reports whether `channel` matches `pattern`, where `pattern` tokens are
+/// separated by `.`: `*` matches exactly one token, and `>` (only valid as
+/// the final token) matches one or more trailing tokens.
+pub fn matches_pattern(channel: &str, pattern: &str) -> bool {
+    let channel_tokens: Vec<&str> = channel.split('.').collect();
+    let pattern_tokens: Vec<&str> = pattern.split('.').collect();
+
+    for (i, ptoken) in pattern_tokens.iter().enumerate() {
+        if *ptoken == ">" {
+            return i < channel_tokens.len();
+        }
+        match channel_tokens.get(i) {
+            Some(ctoken) if *ptoken == "*" || ctoken == ptoken => continue,
+            _ => return false,
+        }
+    }
+    channel_tokens.len() == pattern_tokens.len()
+}
+
+/// Subscribes to every channel on `processid` (from `spec.channels`, as
+/// returned by [`crate::get_process`]) whose name matches `pattern`, fanning
+/// their entries into one `callback` tagged with the originating channel
+/// name. Each matching channel runs its own underlying
+/// [`crate::stream::subscribe_channel_stream`] long poll concurrently,
+/// resubscribing from its own advancing cursor; `callback` is invoked with
+/// whichever batch of entries is ready next (everything already buffered
+/// for the fan-in channel, collapsed into one call instead of one callback
+/// invocation per entry). Delivery into the fan-in channel is backpressured
+/// with `tx.reserve().await`, the same pattern
+/// [`crate::rpc::send_ws_subscribe_channel_stream`] uses, so a slow
+/// `callback` blocks the underlying long polls rather than entries being
+/// dropped once the channel's buffer fills. Returning `false` from
+/// `callback` ends every subscription, matching `subscribe_channel`'s
+/// single-channel contract.
+pub async fn subscribe_channels<F>(
+    processid: &str,
+    pattern: &str,
+    afterseq: i64,
+    timeout: i32,
+    prvkey: &str,
+    mut callback: F,
+) -> Result<(), RPCError>
+where
+    F: FnMut(Vec<TaggedChannelEntry>) -> bool,
+{
+    let process = crate::get_process(processid, prvkey).await?;
+    let matching: Vec<String> = process.spec.channels.into_iter().filter(|name| matches_pattern(name, pattern)).collect();
+
+    if matching.is_empty() {
+        return Ok(());
+    }
+
+    let (tx, mut rx) = mpsc::channel::<TaggedChannelEntry>(256);
+    for channel in matching {
+        let tx = tx.clone();
+        let mut sub = crate::stream::subscribe_channel_stream(processid, &channel, afterseq, timeout, prvkey);
+
+        crate::rt::spawn(async move {
+            while let Some(entry) = sub.recv().await {
+                let permit = match tx.reserve().await {
+                    Ok(permit) => permit,
+                    Err(_) => return, // fan-in receiver dropped; stop polling.
+                };
+                permit.send(TaggedChannelEntry { channel: channel.clone(), entry });
+            }
+        });
+    }
+    drop(tx);
+
+    while let Some(first) = rx.recv().await {
+        let mut batch = vec![first];
+        while let Ok(next) = rx.try_recv() {
+            batch.push(next);
+        }
+        if !callback(batch) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_pattern_exact() {
+        assert!(matches_pattern("logs.stdout", "logs.stdout"));
+        assert!(!matches_pattern("logs.stdout", "logs.stderr"));
+    }
+
+    #[test]
+    fn test_matches_pattern_single_token_wildcard() {
+        assert!(matches_pattern("logs.stdout", "logs.*"));
+        assert!(matches_pattern("logs.stderr", "logs.*"));
+        assert!(!matches_pattern("logs.worker.1", "logs.*"));
+    }
+
+    #[test]
+    fn test_matches_pattern_trailing_wildcard() {
+        assert!(matches_pattern("logs.stderr", "logs.>"));
+        assert!(matches_pattern("logs.worker.1", "logs.>"));
+        assert!(!matches_pattern("metrics.cpu", "logs.>"));
+    }
+
+    #[test]
+    fn test_matches_pattern_trailing_wildcard_requires_at_least_one_token() {
+        assert!(!matches_pattern("logs", "logs.>"));
+    }
+
+    #[test]
+    fn test_matches_pattern_wildcard_mid_pattern() {
+        assert!(matches_pattern("logs.worker.1", "logs.*.1"));
+        assert!(!matches_pattern("logs.worker.2", "logs.*.1"));
+    }
+
+    // `subscribe_channels` can't be exercised end-to-end in tests (it dials
+    // a real websocket via `crate::stream::subscribe_channel_stream`), but
+    // the fix here is specifically the delivery discipline feeding its
+    // fan-in `mpsc::Sender<TaggedChannelEntry>`: reserve a slot before
+    // advancing past an entry instead of `try_send`-and-drop on a full
+    // channel. Exercise that discipline directly against the same bounded
+    // channel and a consumer slower than the producers.
+    #[tokio::test]
+    async fn test_backpressured_fanin_drops_no_entries_under_slow_consumer() {
+        const CHANNELS: i64 = 3;
+        const PER_CHANNEL: i64 = 200; // 600 total, well past the 256 buffer.
+
+        let (tx, mut rx) = mpsc::channel::<TaggedChannelEntry>(256);
+
+        for c in 0..CHANNELS {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                for seq in 0..PER_CHANNEL {
+                    let permit = match tx.reserve().await {
+                        Ok(permit) => permit,
+                        Err(_) => return,
+                    };
+                    permit.send(TaggedChannelEntry {
+                        channel: format!("chan-{}", c),
+                        entry: ChannelEntry { sequence: seq, ..Default::default() },
+                    });
+                }
+            });
+        }
+        drop(tx);
+
+        let mut received = 0i64;
+        while rx.recv().await.is_some() {
+            received += 1;
+            // Slow consumer: let the producers' buffered backlog build up
+            // past the channel's capacity before draining further.
+            if received % 50 == 0 {
+                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+            }
+        }
+
+        assert_eq!(received, CHANNELS * PER_CHANNEL);
+    }
+}
@@ -0,0 +1,364 @@
+//! Docker container-backed executor.
+//!
+//! `ExecutorRuntime` and `Executor` dispatch an assigned `Process` to a
+//! Rust closure; `ContainerExecutor` instead runs it inside a Docker
+//! container, the same way `capability.rs` shells out to the `docker` CLI
+//! rather than linking a Docker API client. A function name is registered
+//! with a [`ContainerSpec`] (image plus an optional result file path); on
+//! assignment the container is launched with the spec's `args`/`env`/`fs`
+//! mounts, its stdout/stderr are streamed line-by-line through `add_log` in
+//! real time, and the exit code decides `close`/`fail_with` the same way
+//! `dispatch` does for native handlers. A configured result file is read
+//! back and set as the process output via `set_output` before closing.
+
+use crate::core::{Log, Process};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+/// Per-function container configuration.
+#[derive(Debug, Clone)]
+pub struct ContainerSpec {
+    /// Docker image to run, e.g. `"python:3.12-slim"`.
+    pub image: String,
+    /// Path, inside the container, read after a successful exit and set as
+    /// the process output (one line per output value). `None` means the
+    /// process is closed with no output.
+    pub result_file: Option<String>,
+}
+
+impl ContainerSpec {
+    pub fn new(image: &str) -> ContainerSpec {
+        ContainerSpec {
+            image: image.to_owned(),
+            result_file: None,
+        }
+    }
+
+    /// Sets the in-container path to read back as output on success.
+    pub fn result_file(mut self, path: &str) -> ContainerSpec {
+        self.result_file = Some(path.to_owned());
+        self
+    }
+}
+
+/// Builder for a long-running container-backed executor.
+///
+/// # Example
+/// ```rust,no_run
+/// use colonyos::container::{ContainerExecutor, ContainerSpec};
+///
+/// # async fn run() {
+/// ContainerExecutor::new("mycolony", "prvkey")
+///     .register_container("echo", ContainerSpec::new("busybox").result_file("/out/result"))
+///     .run()
+///     .await;
+/// # }
+/// ```
+pub struct ContainerExecutor {
+    colonyname: String,
+    prvkey: String,
+    assign_timeout: i32,
+    concurrency: usize,
+    containers: HashMap<String, ContainerSpec>,
+    shutdown: CancellationToken,
+}
+
+impl ContainerExecutor {
+    pub fn new(colonyname: &str, prvkey: &str) -> ContainerExecutor {
+        ContainerExecutor {
+            colonyname: colonyname.to_owned(),
+            prvkey: prvkey.to_owned(),
+            assign_timeout: 10,
+            concurrency: 4,
+            containers: HashMap::new(),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Sets the long-poll timeout (seconds) used for each `assign` call.
+    pub fn assign_timeout(mut self, seconds: i32) -> ContainerExecutor {
+        self.assign_timeout = seconds;
+        self
+    }
+
+    /// Bounds the number of containers that may run concurrently.
+    pub fn concurrency(mut self, limit: usize) -> ContainerExecutor {
+        self.concurrency = limit;
+        self
+    }
+
+    /// Registers the container a function name should run in.
+    pub fn register_container(mut self, funcname: &str, spec: ContainerSpec) -> ContainerExecutor {
+        self.containers.insert(funcname.to_owned(), spec);
+        self
+    }
+
+    /// Returns a handle that can be used to trigger a cooperative shutdown
+    /// from outside `run()`.
+    pub fn shutdown_handle(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Drives the assign -> run-container -> close/fail loop until a
+    /// shutdown signal (SIGINT/SIGTERM) or the `shutdown_handle()` token
+    /// fires.
+    pub async fn run(self) {
+        let semaphore = Arc::new(Semaphore::new(self.concurrency.max(1)));
+        let containers = Arc::new(self.containers);
+        let colonyname = self.colonyname.clone();
+        let prvkey = Arc::new(self.prvkey.clone());
+
+        loop {
+            tokio::select! {
+                _ = shutdown_signal() => {
+                    break;
+                }
+                _ = self.shutdown.cancelled() => {
+                    break;
+                }
+                res = crate::assign(&colonyname, self.assign_timeout, &prvkey) => {
+                    match res {
+                        Ok(process) => {
+                            let permit = semaphore.clone().acquire_owned().await.unwrap();
+                            let containers = containers.clone();
+                            let prvkey = prvkey.clone();
+                            tokio::spawn(async move {
+                                dispatch_container(process, containers, prvkey).await;
+                                drop(permit);
+                            });
+                        }
+                        Err(_) => {
+                            // Connection errors and assign timeouts are
+                            // both retried immediately; containerized
+                            // functions are expected to run on a stable,
+                            // locally-reachable server.
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Builds the `docker run` arguments for `spec` against `process`: the
+/// image, `--rm`, an `-e` flag per `process.spec.env` entry, a `-v` flag
+/// per `process.spec.fs.dirs` entry (mounted at the same path inside the
+/// container as its `dir`), and `process.spec.args` as the command.
+fn build_docker_args(spec: &ContainerSpec, process: &Process) -> Vec<String> {
+    let mut args = vec!["run".to_owned(), "--rm".to_owned()];
+
+    for (key, value) in &process.spec.env {
+        args.push("-e".to_owned());
+        args.push(format!("{key}={value}"));
+    }
+
+    for dir in &process.spec.fs.dirs {
+        args.push("-v".to_owned());
+        args.push(format!("{0}:{0}", dir.dir));
+    }
+
+    args.push(spec.image.clone());
+    args.extend(process.spec.args.clone());
+    args
+}
+
+async fn dispatch_container(process: Process, containers: Arc<HashMap<String, ContainerSpec>>, prvkey: Arc<String>) {
+    let processid = process.processid.clone();
+    let colonyname = process.spec.conditions.colonyname.clone();
+    let funcname = process.spec.funcname.clone();
+
+    let spec = match containers.get(&funcname) {
+        Some(s) => s.clone(),
+        None => {
+            let _ = crate::fail_with(&processid, &format!("unknown function: {funcname}"), &prvkey).await;
+            return;
+        }
+    };
+
+    let args = build_docker_args(&spec, &process);
+    let mut child = match Command::new("docker")
+        .args(&args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            let _ = crate::fail_with(&processid, &format!("failed to start container: {e}"), &prvkey).await;
+            return;
+        }
+    };
+
+    let stdout = child.stdout.take().expect("piped stdout");
+    let stderr = child.stderr.take().expect("piped stderr");
+    let stderr_tail = Arc::new(std::sync::Mutex::new(String::new()));
+
+    let stdout_task = stream_to_logs(stdout, processid.clone(), colonyname.clone(), funcname.clone(), prvkey.clone());
+    let stderr_task = {
+        let stderr_tail = stderr_tail.clone();
+        let processid = processid.clone();
+        let colonyname = colonyname.clone();
+        let funcname = funcname.clone();
+        let prvkey = prvkey.clone();
+        async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                *stderr_tail.lock().unwrap() = line.clone();
+                let _ = crate::add_log(
+                    &Log {
+                        processid: processid.clone(),
+                        colonyname: colonyname.clone(),
+                        executorname: funcname.clone(),
+                        message: line,
+                        timestamp: "0".to_string(),
+                    },
+                    &prvkey,
+                )
+                .await;
+            }
+        }
+    };
+
+    tokio::join!(stdout_task, stderr_task);
+
+    let status = match child.wait().await {
+        Ok(status) => status,
+        Err(e) => {
+            let _ = crate::fail_with(&processid, &format!("container wait failed: {e}"), &prvkey).await;
+            return;
+        }
+    };
+
+    if status.success() {
+        if let Some(result_file) = &spec.result_file {
+            if let Ok(contents) = tokio::fs::read_to_string(result_file).await {
+                let output: Vec<String> = contents.lines().map(str::to_owned).collect();
+                let _ = crate::set_output(&processid, output, &prvkey).await;
+            }
+        }
+        let _ = crate::close(&processid, &prvkey).await;
+    } else {
+        let message = stderr_tail.lock().unwrap().clone();
+        let message = if message.is_empty() {
+            format!("container exited with status {status}")
+        } else {
+            message
+        };
+        let _ = crate::fail_with(&processid, &message, &prvkey).await;
+    }
+}
+
+async fn stream_to_logs(
+    stdout: tokio::process::ChildStdout,
+    processid: String,
+    colonyname: String,
+    funcname: String,
+    prvkey: Arc<String>,
+) {
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let _ = crate::add_log(
+            &Log {
+                processid: processid.clone(),
+                colonyname: colonyname.clone(),
+                executorname: funcname.clone(),
+                message: line,
+                timestamp: "0".to_string(),
+            },
+            &prvkey,
+        )
+        .await;
+    }
+}
+
+#[cfg(unix)]
+async fn shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut sigint = signal(SignalKind::interrupt()).expect("failed to install SIGINT handler");
+    let mut sigterm = signal(SignalKind::terminate()).expect("failed to install SIGTERM handler");
+    tokio::select! {
+        _ = sigint.recv() => {}
+        _ = sigterm.recv() => {}
+    }
+}
+
+#[cfg(not(unix))]
+async fn shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::SyncDirMount;
+
+    fn process_with(funcname: &str, args: Vec<&str>) -> Process {
+        let mut spec = crate::core::FunctionSpec::new(funcname, "container-executor", "mycolony");
+        spec.args = args.into_iter().map(String::from).collect();
+        Process {
+            processid: "process-123".to_owned(),
+            initiatorid: String::new(),
+            initiatorname: String::new(),
+            assignedexecutorid: String::new(),
+            isassigned: false,
+            state: crate::core::ProcessState::Waiting,
+            prioritytime: 0,
+            submissiontime: crate::core::colony_date_epoch(),
+            starttime: crate::core::colony_date_epoch(),
+            endtime: crate::core::colony_date_epoch(),
+            waitdeadline: crate::core::colony_date_epoch(),
+            execdeadline: crate::core::colony_date_epoch(),
+            retries: 0,
+            attributes: Vec::new(),
+            spec,
+            waitforparents: false,
+            parents: Vec::new(),
+            children: Vec::new(),
+            processgraphid: String::new(),
+            input: Vec::new(),
+            output: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_container_spec_defaults_to_no_result_file() {
+        let spec = ContainerSpec::new("busybox");
+        assert_eq!(spec.image, "busybox");
+        assert!(spec.result_file.is_none());
+    }
+
+    #[test]
+    fn test_build_docker_args_includes_image_and_command() {
+        let spec = ContainerSpec::new("busybox");
+        let process = process_with("echo", vec!["hello"]);
+        let args = build_docker_args(&spec, &process);
+        assert_eq!(args, vec!["run", "--rm", "busybox", "hello"]);
+    }
+
+    #[test]
+    fn test_build_docker_args_adds_env_and_volume_flags() {
+        let spec = ContainerSpec::new("busybox");
+        let mut process = process_with("echo", vec!["hello"]);
+        process.spec.env.insert("FOO".to_owned(), "bar".to_owned());
+        process.spec.fs.dirs.push(SyncDirMount {
+            label: "data".to_owned(),
+            dir: "/data".to_owned(),
+            keepfiles: false,
+            onconflicts: crate::core::ConflictResolution {
+                onstart: crate::core::OnStart { keeplocal: false },
+                onclose: crate::core::OnClose { keeplocal: false },
+            },
+        });
+        let args = build_docker_args(&spec, &process);
+        assert!(args.contains(&"-e".to_owned()));
+        assert!(args.contains(&"FOO=bar".to_owned()));
+        assert!(args.contains(&"-v".to_owned()));
+        assert!(args.contains(&"/data:/data".to_owned()));
+    }
+}
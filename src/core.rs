@@ -1,6 +1,6 @@
 //! Core types for ColonyOS SDK
 
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 use std::collections::HashMap;
 
@@ -29,6 +29,272 @@ pub const RUNNING: i32 = 1;
 pub const SUCCESS: i32 = 2;
 pub const FAILED: i32 = 3;
 
+// ============== Status Enums ==============
+//
+// `Process`/`ProcessGraph`/`Executor`/`Attribute` used to store their
+// status fields as bare `i32`s compared against the constants above, so
+// nothing stopped an invalid value from round-tripping through the SDK.
+// The enums below wrap the same wire integers in a typed, exhaustively
+// matchable form.
+//
+// `serde_repr`'s derive macros only support purely fieldless enums (the
+// whole discriminant is the wire value), so they can't express the
+// `Unknown(i32)` catch-all a forward-compatible client needs for a status
+// value a newer server might send. These enums hand-write `Serialize`/
+// `Deserialize` to the same bare-integer wire contract `serde_repr` would
+// produce, plus that catch-all.
+
+/// `Process.state` / `ProcessGraph.state`. Wire-compatible with the
+/// `WAITING`/`RUNNING`/`SUCCESS`/`FAILED` constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessState {
+    Waiting,
+    Running,
+    Success,
+    Failed,
+    /// A status value this SDK version doesn't recognize yet.
+    Unknown(i32),
+}
+
+impl ProcessState {
+    fn to_i32(self) -> i32 {
+        match self {
+            ProcessState::Waiting => WAITING,
+            ProcessState::Running => RUNNING,
+            ProcessState::Success => SUCCESS,
+            ProcessState::Failed => FAILED,
+            ProcessState::Unknown(v) => v,
+        }
+    }
+
+    fn from_i32(v: i32) -> ProcessState {
+        match v {
+            WAITING => ProcessState::Waiting,
+            RUNNING => ProcessState::Running,
+            SUCCESS => ProcessState::Success,
+            FAILED => ProcessState::Failed,
+            other => ProcessState::Unknown(other),
+        }
+    }
+}
+
+impl Default for ProcessState {
+    fn default() -> ProcessState {
+        ProcessState::Waiting
+    }
+}
+
+impl Serialize for ProcessState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(self.to_i32())
+    }
+}
+
+impl<'de> Deserialize<'de> for ProcessState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ProcessState, D::Error> {
+        Ok(ProcessState::from_i32(i32::deserialize(deserializer)?))
+    }
+}
+
+/// `Executor.state`: whether an executor has been approved to join a
+/// colony. Wire-compatible with the `PENDING`/`APPROVED`/`REJECTED`
+/// constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutorState {
+    Pending,
+    Approved,
+    Rejected,
+    /// A status value this SDK version doesn't recognize yet.
+    Unknown(i32),
+}
+
+impl ExecutorState {
+    fn to_i32(self) -> i32 {
+        match self {
+            ExecutorState::Pending => PENDING,
+            ExecutorState::Approved => APPROVED,
+            ExecutorState::Rejected => REJECTED,
+            ExecutorState::Unknown(v) => v,
+        }
+    }
+
+    fn from_i32(v: i32) -> ExecutorState {
+        match v {
+            PENDING => ExecutorState::Pending,
+            APPROVED => ExecutorState::Approved,
+            REJECTED => ExecutorState::Rejected,
+            other => ExecutorState::Unknown(other),
+        }
+    }
+}
+
+impl Default for ExecutorState {
+    fn default() -> ExecutorState {
+        ExecutorState::Pending
+    }
+}
+
+impl Serialize for ExecutorState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(self.to_i32())
+    }
+}
+
+impl<'de> Deserialize<'de> for ExecutorState {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<ExecutorState, D::Error> {
+        Ok(ExecutorState::from_i32(i32::deserialize(deserializer)?))
+    }
+}
+
+/// `Attribute.attributetype`: which of a process's four attribute
+/// directions (input/output/error/environment) an attribute belongs to.
+/// Wire-compatible with the `IN`/`OUT`/`ERR`/`ENV` constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeType {
+    In,
+    Out,
+    Err,
+    Env,
+    /// A status value this SDK version doesn't recognize yet.
+    Unknown(i32),
+}
+
+impl AttributeType {
+    fn to_i32(self) -> i32 {
+        match self {
+            AttributeType::In => IN,
+            AttributeType::Out => OUT,
+            AttributeType::Err => ERR,
+            AttributeType::Env => ENV,
+            AttributeType::Unknown(v) => v,
+        }
+    }
+
+    fn from_i32(v: i32) -> AttributeType {
+        match v {
+            IN => AttributeType::In,
+            OUT => AttributeType::Out,
+            ERR => AttributeType::Err,
+            ENV => AttributeType::Env,
+            other => AttributeType::Unknown(other),
+        }
+    }
+}
+
+impl Default for AttributeType {
+    fn default() -> AttributeType {
+        AttributeType::In
+    }
+}
+
+impl Serialize for AttributeType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i32(self.to_i32())
+    }
+}
+
+impl<'de> Deserialize<'de> for AttributeType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<AttributeType, D::Error> {
+        Ok(AttributeType::from_i32(i32::deserialize(deserializer)?))
+    }
+}
+
+// ============== Typed Timestamps (optional `chrono` feature) ==============
+//
+// `Process`'s `submissiontime`/`starttime`/`endtime`/`waitdeadline`/
+// `execdeadline`, `Executor`'s `commissiontime`/`lastheardfromtime`,
+// `Blueprint.lastreconciled` and `ChannelEntry.timestamp` are all RFC3339
+// strings on the wire; `Log.timestamp` is unix milliseconds. All of them
+// used to be stored as their raw wire type, so a caller wanting duration
+// or ordering comparisons had to parse them by hand first. With the
+// `chrono` feature enabled, [`ColonyDate`] is `chrono::DateTime<Utc>` and
+// every one of those fields carries a real, comparable timestamp; without
+// it, `ColonyDate` is plain `String` and the crate carries one fewer
+// dependency. Mirrors `bollard-stubs`' feature-gated `BollardDate` /
+// RFC3339 `deserialize_timestamp` and `shiplift`'s
+// `datetime_from_unix_timestamp`.
+#[cfg(feature = "chrono")]
+pub type ColonyDate = chrono::DateTime<chrono::Utc>;
+#[cfg(not(feature = "chrono"))]
+pub type ColonyDate = String;
+
+#[cfg(feature = "chrono")]
+pub(crate) fn colony_date_epoch() -> ColonyDate {
+    chrono::DateTime::<chrono::Utc>::from_timestamp(0, 0).unwrap()
+}
+#[cfg(not(feature = "chrono"))]
+pub(crate) fn colony_date_epoch() -> ColonyDate {
+    String::new()
+}
+
+pub(crate) fn is_epoch_colony_date(v: &ColonyDate) -> bool {
+    *v == colony_date_epoch()
+}
+
+/// Parses an RFC3339 wire string, treating an empty string as the epoch
+/// rather than failing.
+#[cfg(feature = "chrono")]
+fn deserialize_colony_date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ColonyDate, D::Error> {
+    let s = String::deserialize(deserializer)?;
+    if s.is_empty() {
+        return Ok(colony_date_epoch());
+    }
+    chrono::DateTime::parse_from_rfc3339(&s)
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+        .map_err(serde::de::Error::custom)
+}
+#[cfg(not(feature = "chrono"))]
+fn deserialize_colony_date<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ColonyDate, D::Error> {
+    deserialize_null_default(deserializer)
+}
+
+#[cfg(feature = "chrono")]
+fn serialize_colony_date<S: Serializer>(date: &ColonyDate, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&date.to_rfc3339())
+}
+#[cfg(not(feature = "chrono"))]
+fn serialize_colony_date<S: Serializer>(date: &ColonyDate, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(date)
+}
+
+/// `Log.timestamp` is unix milliseconds on the wire, not RFC3339.
+#[cfg(feature = "chrono")]
+fn deserialize_log_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ColonyDate, D::Error> {
+    use chrono::TimeZone;
+    let millis = i64::deserialize(deserializer)?;
+    chrono::Utc
+        .timestamp_millis_opt(millis)
+        .single()
+        .ok_or_else(|| serde::de::Error::custom(format!("invalid unix-millis timestamp: {millis}")))
+}
+#[cfg(not(feature = "chrono"))]
+fn deserialize_log_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<ColonyDate, D::Error> {
+    Ok(i64::deserialize(deserializer)?.to_string())
+}
+
+#[cfg(feature = "chrono")]
+fn serialize_log_timestamp<S: Serializer>(date: &ColonyDate, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(date.timestamp_millis())
+}
+#[cfg(not(feature = "chrono"))]
+fn serialize_log_timestamp<S: Serializer>(date: &ColonyDate, serializer: S) -> Result<S::Ok, S::Error> {
+    let millis: i64 = date.parse().map_err(serde::ser::Error::custom)?;
+    serializer.serialize_i64(millis)
+}
+
+/// Converts a `Log.timestamp` back to unix milliseconds, for callers (e.g.
+/// [`crate::stream`]'s `since`-cursor polling) that need to compare
+/// timestamps numerically regardless of whether the `chrono` feature is
+/// enabled.
+#[cfg(feature = "chrono")]
+pub(crate) fn colony_date_as_millis(date: &ColonyDate) -> i64 {
+    date.timestamp_millis()
+}
+#[cfg(not(feature = "chrono"))]
+pub(crate) fn colony_date_as_millis(date: &ColonyDate) -> i64 {
+    date.parse().unwrap_or(0)
+}
+
 // ============== Error Types ==============
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -126,10 +392,105 @@ pub struct Allocations {
     pub projects: HashMap<String, Project>,
 }
 
+/// The `allocated*`/`used*` dimension that a [`QuotaError`] was raised
+/// against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaDimension {
+    Cpu,
+    Gpu,
+    Storage,
+}
+
+impl std::fmt::Display for QuotaDimension {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            QuotaDimension::Cpu => "cpu",
+            QuotaDimension::Gpu => "gpu",
+            QuotaDimension::Storage => "storage",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Returned by [`Allocations::try_reserve`] when a reservation would
+/// overcommit a project's quota.
+#[derive(Debug, Clone)]
+pub struct QuotaError {
+    pub project: String,
+    pub dimension: QuotaDimension,
+    pub requested: i64,
+    pub remaining: i64,
+}
+
+impl std::fmt::Display for QuotaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "project {} would exceed {} quota: requested {} but only {} remain",
+            self.project, self.dimension, self.requested, self.remaining
+        )
+    }
+}
+
 impl Allocations {
     pub fn is_empty(&self) -> bool {
         self.projects.is_empty()
     }
+
+    /// Reserves `cpu`/`gpu`/`storage` against `project`'s remaining headroom
+    /// (`allocated - used`), bumping the `used*` counters on success. A
+    /// project with no prior allocation is treated as having zero headroom.
+    /// Fails without mutating anything if any dimension would be
+    /// overcommitted.
+    pub fn try_reserve(&mut self, project: &str, cpu: i64, gpu: i64, storage: i64) -> Result<(), QuotaError> {
+        let p = self.projects.get(project).cloned().unwrap_or_default();
+
+        let remaining_cpu = p.allocatedcpu - p.usedcpu;
+        if cpu > remaining_cpu {
+            return Err(QuotaError {
+                project: project.to_owned(),
+                dimension: QuotaDimension::Cpu,
+                requested: cpu,
+                remaining: remaining_cpu,
+            });
+        }
+
+        let remaining_gpu = p.allocatedgpu - p.usedgpu;
+        if gpu > remaining_gpu {
+            return Err(QuotaError {
+                project: project.to_owned(),
+                dimension: QuotaDimension::Gpu,
+                requested: gpu,
+                remaining: remaining_gpu,
+            });
+        }
+
+        let remaining_storage = p.allocatedstorage - p.usedstorage;
+        if storage > remaining_storage {
+            return Err(QuotaError {
+                project: project.to_owned(),
+                dimension: QuotaDimension::Storage,
+                requested: storage,
+                remaining: remaining_storage,
+            });
+        }
+
+        let p = self.projects.entry(project.to_owned()).or_default();
+        p.usedcpu += cpu;
+        p.usedgpu += gpu;
+        p.usedstorage += storage;
+        Ok(())
+    }
+
+    /// Releases a previous reservation, decrementing the `used*` counters.
+    /// Counters are floored at zero so an over-release can't go negative.
+    pub fn release(&mut self, project: &str, cpu: i64, gpu: i64, storage: i64) {
+        if let Some(p) = self.projects.get_mut(project) {
+            p.usedcpu = (p.usedcpu - cpu).max(0);
+            p.usedgpu = (p.usedgpu - gpu).max(0);
+            p.usedstorage = (p.usedstorage - storage).max(0);
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -142,14 +503,24 @@ pub struct Executor {
     pub executorname: String,
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub colonyname: String,
-    #[serde(default, skip_serializing_if = "is_zero_i32")]
-    pub state: i32,
+    #[serde(default, skip_serializing_if = "is_pending")]
+    pub state: ExecutorState,
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     pub requirefuncreg: bool,
-    #[serde(default, deserialize_with = "deserialize_null_default", skip_serializing_if = "String::is_empty")]
-    pub commissiontime: String,
-    #[serde(default, deserialize_with = "deserialize_null_default", skip_serializing_if = "String::is_empty")]
-    pub lastheardfromtime: String,
+    #[serde(
+        default = "colony_date_epoch",
+        deserialize_with = "deserialize_colony_date",
+        serialize_with = "serialize_colony_date",
+        skip_serializing_if = "is_epoch_colony_date"
+    )]
+    pub commissiontime: ColonyDate,
+    #[serde(
+        default = "colony_date_epoch",
+        deserialize_with = "deserialize_colony_date",
+        serialize_with = "serialize_colony_date",
+        skip_serializing_if = "is_epoch_colony_date"
+    )]
+    pub lastheardfromtime: ColonyDate,
     #[serde(default, deserialize_with = "deserialize_null_default", skip_serializing_if = "String::is_empty")]
     pub locationname: String,
     #[serde(default, deserialize_with = "deserialize_null_default", skip_serializing_if = "Capabilities::is_empty")]
@@ -164,6 +535,7 @@ pub struct Executor {
 
 fn is_zero_i32(v: &i32) -> bool { *v == 0 }
 fn is_zero_i64(v: &i64) -> bool { *v == 0 }
+fn is_pending(v: &ExecutorState) -> bool { *v == ExecutorState::Pending }
 
 impl Executor {
     pub fn new(name: &str, executorid: &str, executortype: &str, colonyname: &str) -> Executor {
@@ -172,10 +544,10 @@ impl Executor {
             executortype: executortype.to_owned(),
             executorname: name.to_owned(),
             colonyname: colonyname.to_owned(),
-            state: 0,
+            state: ExecutorState::Pending,
             requirefuncreg: false,
-            commissiontime: String::new(),
-            lastheardfromtime: String::new(),
+            commissiontime: colony_date_epoch(),
+            lastheardfromtime: colony_date_epoch(),
             locationname: String::new(),
             capabilities: Capabilities::default(),
             allocations: Allocations::default(),
@@ -353,7 +725,7 @@ pub struct Attribute {
     #[serde(default)]
     pub targetprocessgraphid: String,
     #[serde(default)]
-    pub attributetype: i32,
+    pub attributetype: AttributeType,
     pub key: String,
     pub value: String,
 }
@@ -365,7 +737,7 @@ impl Attribute {
             targetid: processid.to_owned(),
             targetcolonyname: colonyname.to_owned(),
             targetprocessgraphid: String::new(),
-            attributetype: OUT,
+            attributetype: AttributeType::Out,
             key: key.to_owned(),
             value: value.to_owned(),
         }
@@ -386,19 +758,39 @@ pub struct Process {
     #[serde(default)]
     pub isassigned: bool,
     #[serde(default)]
-    pub state: i32,
+    pub state: ProcessState,
     #[serde(default)]
     pub prioritytime: i64,
-    #[serde(default, deserialize_with = "deserialize_null_default")]
-    pub submissiontime: String,
-    #[serde(default, deserialize_with = "deserialize_null_default")]
-    pub starttime: String,
-    #[serde(default, deserialize_with = "deserialize_null_default")]
-    pub endtime: String,
-    #[serde(default, deserialize_with = "deserialize_null_default")]
-    pub waitdeadline: String,
-    #[serde(default, deserialize_with = "deserialize_null_default")]
-    pub execdeadline: String,
+    #[serde(
+        default = "colony_date_epoch",
+        deserialize_with = "deserialize_colony_date",
+        serialize_with = "serialize_colony_date"
+    )]
+    pub submissiontime: ColonyDate,
+    #[serde(
+        default = "colony_date_epoch",
+        deserialize_with = "deserialize_colony_date",
+        serialize_with = "serialize_colony_date"
+    )]
+    pub starttime: ColonyDate,
+    #[serde(
+        default = "colony_date_epoch",
+        deserialize_with = "deserialize_colony_date",
+        serialize_with = "serialize_colony_date"
+    )]
+    pub endtime: ColonyDate,
+    #[serde(
+        default = "colony_date_epoch",
+        deserialize_with = "deserialize_colony_date",
+        serialize_with = "serialize_colony_date"
+    )]
+    pub waitdeadline: ColonyDate,
+    #[serde(
+        default = "colony_date_epoch",
+        deserialize_with = "deserialize_colony_date",
+        serialize_with = "serialize_colony_date"
+    )]
+    pub execdeadline: ColonyDate,
     #[serde(default)]
     pub retries: i32,
     #[serde(default, deserialize_with = "deserialize_null_default")]
@@ -429,11 +821,19 @@ pub struct ProcessGraph {
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub colonyname: String,
     #[serde(default)]
-    pub state: i32,
+    pub state: ProcessState,
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub rootprocessids: Vec<String>,
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub processids: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub waitingids: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub runningids: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub successfulids: Vec<String>,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub failedids: Vec<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -456,13 +856,17 @@ pub struct Log {
     pub executorname: String,
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub message: String,
-    #[serde(default)]
-    pub timestamp: i64,
+    #[serde(
+        default = "colony_date_epoch",
+        deserialize_with = "deserialize_log_timestamp",
+        serialize_with = "serialize_log_timestamp"
+    )]
+    pub timestamp: ColonyDate,
 }
 
 // ============== Channel ==============
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ChannelEntry {
     #[serde(default)]
     pub sequence: i64,
@@ -472,12 +876,39 @@ pub struct ChannelEntry {
     pub msgtype: String,
     #[serde(default)]
     pub inreplyto: i64,
-    #[serde(default, deserialize_with = "deserialize_null_default")]
-    pub timestamp: String,
+    #[serde(
+        default = "colony_date_epoch",
+        deserialize_with = "deserialize_colony_date",
+        serialize_with = "serialize_colony_date"
+    )]
+    pub timestamp: ColonyDate,
     #[serde(default, deserialize_with = "deserialize_null_default")]
     pub senderid: String,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub contenttype: String,
 }
 
+impl Default for ChannelEntry {
+    fn default() -> Self {
+        ChannelEntry {
+            sequence: 0,
+            payload: String::new(),
+            msgtype: String::new(),
+            inreplyto: 0,
+            timestamp: colony_date_epoch(),
+            senderid: String::new(),
+            contenttype: String::new(),
+        }
+    }
+}
+
+/// MIME type for a plain UTF-8 string channel payload.
+pub const CONTENT_TYPE_TEXT: &str = "text/plain";
+/// MIME type for a JSON-encoded channel payload.
+pub const CONTENT_TYPE_JSON: &str = "application/json";
+/// MIME type for an untyped binary channel payload.
+pub const CONTENT_TYPE_OCTET_STREAM: &str = "application/octet-stream";
+
 impl ChannelEntry {
     /// Returns the payload decoded from base64 as a UTF-8 string.
     pub fn payload_as_string(&self) -> String {
@@ -493,6 +924,117 @@ impl ChannelEntry {
         use base64::{Engine as _, engine::general_purpose::STANDARD};
         STANDARD.decode(&self.payload).unwrap_or_default()
     }
+
+    /// Base64-decodes `payload` and interprets it according to
+    /// `contenttype`, so a caller doesn't have to know up front whether a
+    /// channel carries JSON, text, or raw bytes. Unrecognized content
+    /// types (e.g. an application-specific one a caller registered with
+    /// the server) fall back to [`ChannelPayload::Other`], keeping both
+    /// the tag and the raw bytes rather than discarding the message.
+    pub fn payload_typed(&self) -> Result<ChannelPayload, ChannelDecodeError> {
+        let bytes = self.payload_bytes();
+        match self.contenttype.as_str() {
+            CONTENT_TYPE_JSON => serde_json::from_slice(&bytes)
+                .map(ChannelPayload::Json)
+                .map_err(|e| ChannelDecodeError::new(format!("invalid JSON channel payload: {e}"))),
+            CONTENT_TYPE_TEXT => String::from_utf8(bytes)
+                .map(ChannelPayload::Text)
+                .map_err(|e| ChannelDecodeError::new(format!("invalid UTF-8 channel payload: {e}"))),
+            CONTENT_TYPE_OCTET_STREAM => Ok(ChannelPayload::Binary(bytes)),
+            other => Ok(ChannelPayload::Other(other.to_owned(), bytes)),
+        }
+    }
+
+    /// Base64-decodes `payload` and deserializes it as `T`, requiring
+    /// `contenttype` to be [`CONTENT_TYPE_JSON`] since only a JSON payload
+    /// can be deserialized into an arbitrary `T`.
+    pub fn decode<T: serde::de::DeserializeOwned>(&self) -> Result<T, ChannelDecodeError> {
+        if self.contenttype != CONTENT_TYPE_JSON {
+            return Err(ChannelDecodeError::new(format!(
+                "cannot decode channel entry with contenttype {:?} as a typed value; expected {CONTENT_TYPE_JSON:?}",
+                self.contenttype
+            )));
+        }
+        serde_json::from_slice(&self.payload_bytes())
+            .map_err(|e| ChannelDecodeError::new(format!("invalid JSON channel payload: {e}")))
+    }
+}
+
+/// A channel entry's payload, decoded according to its `contenttype`
+/// rather than left as an opaque base64 string. Modeled on drogue-ttn's
+/// `Payload` enum, which dispatches on a message-type discriminator to
+/// produce a concrete typed body - here the discriminator is
+/// `ChannelEntry::contenttype` (the field [`CONTENT_TYPE_TEXT`]/
+/// [`CONTENT_TYPE_JSON`]/[`CONTENT_TYPE_OCTET_STREAM`] already populate),
+/// not `msgtype`, which is a free-form application-level routing tag
+/// (e.g. `"data"`) unrelated to how the payload bytes are encoded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChannelPayload {
+    Json(Value),
+    Text(String),
+    Binary(Vec<u8>),
+    /// A content type other than the three above, e.g. one a caller
+    /// registered for an application-specific format; carries the
+    /// content type tag alongside the raw decoded bytes.
+    Other(String, Vec<u8>),
+}
+
+/// Returned by [`ChannelEntry::decode`]/[`ChannelEntry::payload_typed`]
+/// when the payload can't be base64/UTF-8/JSON-decoded as requested.
+#[derive(Debug, Clone)]
+pub struct ChannelDecodeError {
+    pub message: String,
+}
+
+impl ChannelDecodeError {
+    fn new(message: impl Into<String>) -> ChannelDecodeError {
+        ChannelDecodeError { message: message.into() }
+    }
+}
+
+impl std::fmt::Display for ChannelDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ChannelDecodeError {}
+
+/// Opaque, persistable marker for `crate::channel_poll_range`: the highest
+/// sequence number acknowledged so far on one channel. Round-trips through
+/// `serde` so a consumer can save it and resume a poll-range loop exactly
+/// where it left off - even across a reconnect - without re-delivering or
+/// skipping a sequence number.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct ChannelCursor {
+    pub seq: i64,
+}
+
+impl Default for ChannelCursor {
+    /// Starts before the first possible sequence number (`0`).
+    fn default() -> ChannelCursor {
+        ChannelCursor { seq: -1 }
+    }
+}
+
+impl ChannelCursor {
+    /// Starts a cursor before the first sequence number in a channel.
+    pub fn new() -> ChannelCursor {
+        ChannelCursor::default()
+    }
+
+    /// Starts a cursor after `seq` (e.g. a value already acknowledged).
+    pub fn after(seq: i64) -> ChannelCursor {
+        ChannelCursor { seq }
+    }
+
+    /// Advances the cursor to the highest sequence number seen in
+    /// `entries`, never moving it backwards.
+    pub fn advance(&mut self, entries: &[ChannelEntry]) {
+        for entry in entries {
+            self.seq = self.seq.max(entry.sequence);
+        }
+    }
 }
 
 // ============== Statistics ==============
@@ -521,22 +1063,125 @@ pub struct Statistics {
     pub failedworkflows: i64,
 }
 
-// ============== Blueprint ==============
+// ============== Executor Capacity ==============
 
+/// An executor's live, self-reported resource availability, submitted via
+/// `rpc::compose_report_capacity_rpcmsg` and queried back (per colony) via
+/// `rpc::compose_get_capacities_rpcmsg`. Byte quantities are raw counts;
+/// [`ExecutorCapacity::fmt`] renders them as human-readable sizes (KiB/MiB/
+/// GiB) for CLI display, and [`ExecutorCapacity::can_fit`] lets a scheduler
+/// check a candidate executor against a process's resource conditions
+/// before assigning it.
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
-pub struct BlueprintDefinition {
+pub struct ExecutorCapacity {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub executorname: String,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub colonyname: String,
     #[serde(default)]
-    pub name: String,
+    pub freecpucores: i32,
     #[serde(default)]
-    pub colonyname: String,
+    pub freememorybytes: i64,
+    #[serde(default)]
+    pub freediskbytes: i64,
+    #[serde(default)]
+    pub freegpucount: i32,
+}
+
+impl ExecutorCapacity {
+    pub fn new(executorname: &str, colonyname: &str) -> ExecutorCapacity {
+        ExecutorCapacity {
+            executorname: executorname.to_owned(),
+            colonyname: colonyname.to_owned(),
+            freecpucores: 0,
+            freememorybytes: 0,
+            freediskbytes: 0,
+            freegpucount: 0,
+        }
+    }
+
+    /// Whether this capacity can satisfy a process's requested
+    /// `cpu_cores`/`memory_bytes`/`disk_bytes`/`gpu_count`, so a scheduler
+    /// can skip executors that can't fit a pending process.
+    pub fn can_fit(&self, cpu_cores: i32, memory_bytes: i64, disk_bytes: i64, gpu_count: i32) -> bool {
+        self.freecpucores >= cpu_cores
+            && self.freememorybytes >= memory_bytes
+            && self.freediskbytes >= disk_bytes
+            && self.freegpucount >= gpu_count
+    }
+}
+
+fn format_bytes(bytes: i64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    if bytes < 0 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} {}", UNITS[unit])
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+impl std::fmt::Display for ExecutorCapacity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({}): {} free disk, {} free memory, {} CPU cores, {} GPUs",
+            self.executorname,
+            self.colonyname,
+            format_bytes(self.freediskbytes),
+            format_bytes(self.freememorybytes),
+            self.freecpucores,
+            self.freegpucount,
+        )
+    }
+}
+
+// ============== Blueprint ==============
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct BlueprintDefinitionNames {
     #[serde(default)]
     pub kind: String,
     #[serde(default)]
-    pub executortype: String,
+    pub singular: String,
     #[serde(default)]
-    pub specschema: HashMap<String, Value>,
+    pub plural: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct BlueprintDefinitionHandler {
+    #[serde(default, rename = "executorType")]
+    pub executor_type: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct BlueprintDefinitionSpec {
+    #[serde(default)]
+    pub names: BlueprintDefinitionNames,
     #[serde(default)]
-    pub statusschema: HashMap<String, Value>,
+    pub handler: BlueprintDefinitionHandler,
+    /// The JSON Schema that `Blueprint.spec` instances of this kind must
+    /// satisfy. See [`BlueprintDefinition::validate`].
+    #[serde(default)]
+    pub schema: Option<Value>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct BlueprintDefinition {
+    #[serde(default)]
+    pub kind: String,
+    #[serde(default)]
+    pub metadata: BlueprintMetadata,
+    #[serde(default)]
+    pub spec: BlueprintDefinitionSpec,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Default)]
@@ -553,7 +1198,7 @@ pub struct BlueprintHandler {
     pub executortype: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Blueprint {
     #[serde(default)]
     pub blueprintid: String,
@@ -571,8 +1216,62 @@ pub struct Blueprint {
     pub generation: i64,
     #[serde(default)]
     pub reconciledgeneration: i64,
-    #[serde(default)]
-    pub lastreconciled: String,
+    #[serde(
+        default = "colony_date_epoch",
+        deserialize_with = "deserialize_colony_date",
+        serialize_with = "serialize_colony_date"
+    )]
+    pub lastreconciled: ColonyDate,
+}
+
+impl Default for Blueprint {
+    fn default() -> Self {
+        Blueprint {
+            blueprintid: String::new(),
+            kind: String::new(),
+            metadata: BlueprintMetadata::default(),
+            handler: BlueprintHandler::default(),
+            spec: HashMap::new(),
+            status: HashMap::new(),
+            generation: 0,
+            reconciledgeneration: 0,
+            lastreconciled: colony_date_epoch(),
+        }
+    }
+}
+
+/// A blueprint lifecycle/convergence notification, delivered over
+/// [`crate::stream::subscribe_blueprint_events`]'s websocket subscription
+/// instead of a caller re-polling `get_blueprint`. Internally tagged on
+/// `eventtype` so a caller matching on variant gets a typed `blueprint`
+/// straight off the wire, the same way `spec`/`status` decode as typed maps
+/// rather than opaque JSON.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[serde(tag = "eventtype")]
+pub enum BlueprintEvent {
+    /// A new blueprint was created.
+    Added { blueprint: Blueprint },
+    /// `status` changed, independent of whether `generation` caught up with
+    /// `reconciledgeneration`.
+    StatusUpdated { blueprint: Blueprint },
+    /// `reconciledgeneration` caught up with `generation`.
+    Reconciled { blueprint: Blueprint },
+    /// The blueprint was removed.
+    Removed { blueprintid: String },
+}
+
+impl BlueprintEvent {
+    /// The name of the blueprint this event concerns, if the variant
+    /// carries a full `Blueprint` (every variant but `Removed`, which only
+    /// has the id).
+    pub fn blueprint_name(&self) -> Option<&str> {
+        match self {
+            BlueprintEvent::Added { blueprint }
+            | BlueprintEvent::StatusUpdated { blueprint }
+            | BlueprintEvent::Reconciled { blueprint } => Some(blueprint.metadata.name.as_str()),
+            BlueprintEvent::Removed { .. } => None,
+        }
+    }
 }
 
 // ============== Function ==============
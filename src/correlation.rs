@@ -0,0 +1,215 @@
+//! Request/reply correlation over channels using `sequence`/`inreplyto`.
+//!
+//! `ChannelAppendRPCMsg` already carries `sequence`/`inreplyto`, and
+//! `ChannelReadRPCMsg` reads `afterseq` — the same building blocks
+//! Maelstrom uses for `msg_id`/`in_reply_to` RPC — but nothing ties a sent
+//! message to its reply. `channel_request` allocates a monotonically
+//! increasing sequence per `(processid, channelname)`, appends the
+//! message, then registers a `oneshot` sender keyed by that sequence with
+//! the shared [`Dispatcher`] for the channel; `channel_reply` is the
+//! server-side-style counterpart that appends with `inreplyto` set to the
+//! incoming sequence.
+//!
+//! Each `(processid, channelname)` pair is backed by a single background
+//! `subscribe_channel` task (spawned on first use, shared by every
+//! concurrent `channel_request` against that channel) that completes and
+//! removes whichever pending `oneshot` an entry's `inreplyto` matches,
+//! ignoring unmatched entries — a chat-style client that fires off several
+//! tagged requests in flight no longer pays for one subscription per
+//! request.
+
+use crate::core::ChannelEntry;
+use crate::rpc::RPCError;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+static SEQUENCES: Mutex<Option<HashMap<(String, String), i64>>> = Mutex::new(None);
+
+/// Pending `oneshot` replies for one `(processid, channelname)`, keyed by
+/// the `sequence` of the request awaiting a match.
+struct Dispatcher {
+    pending: Mutex<HashMap<i64, oneshot::Sender<ChannelEntry>>>,
+}
+
+// Never removed once created, same as `SEQUENCES`: a process/channel pair
+// is reused for the lifetime of the chat-style session it belongs to, so
+// leaking one long-lived background task per channel is cheaper than
+// tearing down and re-subscribing on every request.
+static DISPATCHERS: Mutex<Option<HashMap<(String, String), Arc<Dispatcher>>>> = Mutex::new(None);
+
+/// Returns the shared [`Dispatcher`] for `(processid, channelname)`,
+/// spawning its background subscription task the first time the pair is
+/// seen.
+fn dispatcher_for(processid: &str, channelname: &str, prvkey: &str) -> Arc<Dispatcher> {
+    let mut guard = DISPATCHERS.lock().unwrap();
+    let dispatchers = guard.get_or_insert_with(HashMap::new);
+    let key = (processid.to_owned(), channelname.to_owned());
+    if let Some(dispatcher) = dispatchers.get(&key) {
+        return dispatcher.clone();
+    }
+
+    let dispatcher = Arc::new(Dispatcher { pending: Mutex::new(HashMap::new()) });
+    dispatchers.insert(key, dispatcher.clone());
+    spawn_dispatch_loop(processid.to_owned(), channelname.to_owned(), prvkey.to_owned(), dispatcher.clone());
+    dispatcher
+}
+
+/// Long-polls `subscribe_channel` forever, handing each entry to whichever
+/// pending `oneshot` its `inreplyto` matches. A connection error retries
+/// after a short delay; any other error (the channel or process no longer
+/// exists) ends the task, leaving any still-pending requests to time out on
+/// their own deadline.
+fn spawn_dispatch_loop(processid: String, channelname: String, prvkey: String, dispatcher: Arc<Dispatcher>) {
+    crate::rt::spawn(async move {
+        let last_seq = Arc::new(AtomicI64::new(0));
+
+        loop {
+            let afterseq = last_seq.load(Ordering::SeqCst);
+            let last_seq_for_cb = last_seq.clone();
+            let dispatcher_for_cb = dispatcher.clone();
+
+            let result = crate::subscribe_channel(&processid, &channelname, afterseq, 30, &prvkey, move |entries| {
+                for entry in entries {
+                    last_seq_for_cb.fetch_max(entry.sequence, Ordering::SeqCst);
+                    if let Some(tx) = dispatcher_for_cb.pending.lock().unwrap().remove(&entry.inreplyto) {
+                        let _ = tx.send(entry);
+                    }
+                }
+                true
+            })
+            .await;
+
+            match result {
+                // A normal server-side long-poll timeout; resubscribe
+                // immediately from the advanced cursor.
+                Ok(_) => {}
+                Err(e) if e.conn_err() => crate::rt::sleep(Duration::from_millis(500)).await,
+                Err(_) => return,
+            }
+        }
+    });
+}
+
+/// Allocates the next sequence number for `(processid, channelname)`, so
+/// concurrent callers targeting the same channel don't collide.
+fn next_sequence(processid: &str, channelname: &str) -> i64 {
+    let mut guard = SEQUENCES.lock().unwrap();
+    let sequences = guard.get_or_insert_with(HashMap::new);
+    let key = (processid.to_owned(), channelname.to_owned());
+    let next = sequences.get(&key).copied().unwrap_or(0) + 1;
+    sequences.insert(key, next);
+    next
+}
+
+/// Returned by [`channel_request`] when it can't complete.
+#[derive(Debug, Clone)]
+pub enum ChannelRequestError {
+    /// The underlying RPC call failed outright.
+    Rpc(RPCError),
+    /// No reply with a matching `inreplyto` arrived before the timeout.
+    Timeout { sequence: i64 },
+}
+
+impl std::fmt::Display for ChannelRequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ChannelRequestError::Rpc(e) => write!(f, "{e}"),
+            ChannelRequestError::Timeout { sequence } => {
+                write!(f, "channel_request: no reply to sequence {sequence} within the timeout")
+            }
+        }
+    }
+}
+
+/// Sends `payload` on `channelname` and awaits a reply (a channel entry
+/// whose `inreplyto` equals the sent sequence) or `timeout`. Registers a
+/// `oneshot` with the channel's shared [`Dispatcher`] before returning, so
+/// several in-flight `channel_request` calls against the same channel are
+/// matched off the same background subscription instead of each opening
+/// their own.
+pub async fn channel_request(
+    processid: &str,
+    channelname: &str,
+    payload: &str,
+    timeout: Duration,
+    prvkey: &str,
+) -> Result<String, ChannelRequestError> {
+    let sequence = next_sequence(processid, channelname);
+    crate::channel_append(
+        processid,
+        channelname,
+        sequence,
+        payload.as_bytes(),
+        crate::core::CONTENT_TYPE_TEXT,
+        0,
+        prvkey,
+    )
+    .await
+    .map_err(ChannelRequestError::Rpc)?;
+
+    let dispatcher = dispatcher_for(processid, channelname, prvkey);
+    let (tx, rx) = oneshot::channel();
+    dispatcher.pending.lock().unwrap().insert(sequence, tx);
+
+    match crate::rt::timeout(timeout, rx).await {
+        Ok(Ok(entry)) => Ok(entry.payload_as_string()),
+        _ => {
+            dispatcher.pending.lock().unwrap().remove(&sequence);
+            Err(ChannelRequestError::Timeout { sequence })
+        }
+    }
+}
+
+/// Server-side-style counterpart to [`channel_request`]: appends `payload`
+/// to `channelname` with `inreplyto` set to `request.sequence`, tying the
+/// reply back to the original request.
+pub async fn channel_reply(
+    processid: &str,
+    channelname: &str,
+    request: &ChannelEntry,
+    payload: &str,
+    prvkey: &str,
+) -> Result<ChannelEntry, RPCError> {
+    let sequence = next_sequence(processid, channelname);
+    crate::channel_append(
+        processid,
+        channelname,
+        sequence,
+        payload.as_bytes(),
+        crate::core::CONTENT_TYPE_TEXT,
+        request.sequence,
+        prvkey,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_sequence_increments_per_key() {
+        let processid = "proc-correlation-test-1";
+        let first = next_sequence(processid, "chan");
+        let second = next_sequence(processid, "chan");
+        assert_eq!(second, first + 1);
+    }
+
+    #[test]
+    fn test_next_sequence_is_independent_per_channel() {
+        let processid = "proc-correlation-test-2";
+        let chan_a = next_sequence(processid, "a");
+        let chan_b = next_sequence(processid, "b");
+        assert_eq!(chan_a, 1);
+        assert_eq!(chan_b, 1);
+    }
+
+    #[test]
+    fn test_channel_request_error_display() {
+        let err = ChannelRequestError::Timeout { sequence: 7 };
+        assert_eq!(format!("{err}"), "channel_request: no reply to sequence 7 within the timeout");
+    }
+}
@@ -0,0 +1,82 @@
+//! `did:key` representation for ColonyOS's secp256k1 identities.
+//!
+//! A ColonyOS member already has a secp256k1 keypair (`crypto::gen_prvkey`/
+//! `crypto::gen_pubkey`), but nothing outside the crate understands that
+//! format. This adds the `did:key` encoding so a member's public key can be
+//! referenced by any DID-aware system: compress the key, prepend the
+//! secp256k1 multicodec prefix, and multibase-encode the result as
+//! base58btc with a `z` prefix. Mirrors the multicodec scheme (and its
+//! `MULTICODEC_K256_BYTES` constant) from adenosine's crypto module.
+
+use crate::crypto::{CryptoError, PubKey};
+use k256::PublicKey;
+
+/// Multicodec prefix for a compressed secp256k1 public key.
+const MULTICODEC_K256_BYTES: [u8; 2] = [0xe7, 0x01];
+
+/// Encodes `pubkey_hex` (an uncompressed secp256k1 public key, as returned
+/// by [`crate::crypto::gen_pubkey`]) as a `did:key:z...` string.
+pub fn did_key_from_pubkey(pubkey_hex: &str) -> Result<String, CryptoError> {
+    let bytes = PubKey::from_hex(pubkey_hex)?;
+    let pubkey = PublicKey::from_sec1_bytes(bytes.as_bytes()).map_err(|e| CryptoError::InvalidPublicKey(e.to_string()))?;
+    let compressed = pubkey.to_encoded_point(true);
+
+    let mut multicodec_bytes = Vec::with_capacity(MULTICODEC_K256_BYTES.len() + compressed.as_bytes().len());
+    multicodec_bytes.extend_from_slice(&MULTICODEC_K256_BYTES);
+    multicodec_bytes.extend_from_slice(compressed.as_bytes());
+
+    Ok(format!("did:key:z{}", bs58::encode(multicodec_bytes).into_string()))
+}
+
+/// Reverses [`did_key_from_pubkey`]: decodes `did`, strips the secp256k1
+/// multicodec prefix, and returns the uncompressed public key as hex, in
+/// the same format [`crate::crypto::gen_pubkey`] produces.
+pub fn pubkey_from_did(did: &str) -> Result<String, CryptoError> {
+    let encoded = did
+        .strip_prefix("did:key:z")
+        .ok_or_else(|| CryptoError::InvalidPublicKey(format!("not a did:key:z string: {did}")))?;
+
+    let bytes = bs58::decode(encoded)
+        .into_vec()
+        .map_err(|e| CryptoError::InvalidPublicKey(format!("invalid base58btc: {e}")))?;
+
+    if bytes.len() < MULTICODEC_K256_BYTES.len() || bytes[..MULTICODEC_K256_BYTES.len()] != MULTICODEC_K256_BYTES {
+        return Err(CryptoError::InvalidPublicKey(
+            "missing or unsupported secp256k1 multicodec prefix".to_owned(),
+        ));
+    }
+
+    let pubkey = PublicKey::from_sec1_bytes(&bytes[MULTICODEC_K256_BYTES.len()..])
+        .map_err(|e| CryptoError::InvalidPublicKey(e.to_string()))?;
+    Ok(hex::encode(pubkey.to_encoded_point(false).as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{gen_prvkey, gen_pubkey};
+
+    #[test]
+    fn test_did_key_roundtrip() {
+        let pubkey = gen_pubkey(&gen_prvkey());
+        let did = did_key_from_pubkey(&pubkey).unwrap();
+        assert!(did.starts_with("did:key:z"));
+        assert_eq!(pubkey_from_did(&did).unwrap(), pubkey);
+    }
+
+    #[test]
+    fn test_did_key_rejects_malformed_pubkey() {
+        assert!(did_key_from_pubkey("not-hex").is_err());
+    }
+
+    #[test]
+    fn test_pubkey_from_did_rejects_missing_prefix() {
+        assert!(pubkey_from_did("not-a-did").is_err());
+    }
+
+    #[test]
+    fn test_pubkey_from_did_rejects_wrong_multicodec() {
+        let bad = format!("did:key:z{}", bs58::encode([0x00, 0x01, 0x02]).into_string());
+        assert!(pubkey_from_did(&bad).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! ECIES: confidential payloads addressed to a ColonyOS member's key.
+//!
+//! `crypto::gen_prvkey`/`crypto::gen_pubkey` give every member a secp256k1
+//! keypair, but there's no way to hand one of them a payload only they can
+//! read. This layers standard ECIES on top: an ephemeral secp256k1 keypair
+//! does ECDH against the recipient's static public key, the shared secret
+//! is hashed with SHA3-256 into a symmetric key, and the payload is sealed
+//! with AES-256-GCM. The output is `ephemeral_pubkey || nonce ||
+//! ciphertext_with_tag`, so [`decrypt`] only needs the recipient's private
+//! key to reverse it. Follows the ECDH-plus-symmetric-cipher construction
+//! from openethereum's crypto utilities, and lets colonies exchange
+//! encrypted process specs keyed to a member's existing identity.
+
+use crate::crypto::{CryptoError, PrvKey, PubKey};
+use aes_gcm::aead::Aead;
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use k256::ecdh::diffie_hellman;
+use k256::{PublicKey, SecretKey};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha3::{Digest, Sha3_256};
+
+const PUBKEY_LEN: usize = 65;
+const NONCE_LEN: usize = 12;
+
+/// Hashes an ECDH shared secret with SHA3-256 into a 32-byte AES-256 key,
+/// reusing the crate's existing hash primitive instead of pulling in HKDF
+/// for a single derivation.
+fn derive_key(shared_secret: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(shared_secret);
+    hasher.finalize().into()
+}
+
+fn parse_public_key(pubkey_hex: &str) -> Result<PublicKey, CryptoError> {
+    let bytes = PubKey::from_hex(pubkey_hex)?;
+    PublicKey::from_sec1_bytes(bytes.as_bytes()).map_err(|e| CryptoError::InvalidPublicKey(e.to_string()))
+}
+
+fn parse_secret_key(prvkey_hex: &str) -> Result<SecretKey, CryptoError> {
+    let bytes = PrvKey::from_hex(prvkey_hex)?;
+    SecretKey::from_slice(bytes.as_bytes()).map_err(|e| CryptoError::InvalidPrivateKey(e.to_string()))
+}
+
+/// Encrypts `plaintext` so that only the holder of the private key behind
+/// `recipient_pubkey_hex` (see [`crate::crypto::gen_pubkey`]) can read it.
+/// Generates a fresh ephemeral keypair per call, so encrypting the same
+/// plaintext twice produces unlinkable ciphertexts.
+pub fn encrypt(recipient_pubkey_hex: &str, plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let recipient_pubkey = parse_public_key(recipient_pubkey_hex)?;
+
+    let ephemeral_secret = SecretKey::random(&mut OsRng);
+    let ephemeral_pubkey = ephemeral_secret.public_key().to_encoded_point(false);
+
+    let shared = diffie_hellman(&ephemeral_secret.to_nonzero_scalar(), recipient_pubkey.as_affine());
+    let key = derive_key(shared.raw_secret_bytes().as_slice());
+
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce_bytes);
+    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+    let ciphertext = cipher
+        .encrypt(nonce, plaintext)
+        .map_err(|e| CryptoError::EncryptionFailed(e.to_string()))?;
+
+    let mut blob = Vec::with_capacity(PUBKEY_LEN + NONCE_LEN + ciphertext.len());
+    blob.extend_from_slice(ephemeral_pubkey.as_bytes());
+    blob.extend_from_slice(&nonce_bytes);
+    blob.extend_from_slice(&ciphertext);
+    Ok(blob)
+}
+
+/// Reverses [`encrypt`]: splits `blob` back into the ephemeral public key,
+/// nonce, and ciphertext, redoes the ECDH with `recipient_prvkey_hex`'s
+/// private key, and decrypts. Fails with [`CryptoError::DecryptionFailed`]
+/// if `blob` was tampered with or encrypted for a different recipient.
+pub fn decrypt(recipient_prvkey_hex: &str, blob: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let recipient_secret = parse_secret_key(recipient_prvkey_hex)?;
+
+    if blob.len() < PUBKEY_LEN + NONCE_LEN {
+        return Err(CryptoError::InvalidLength {
+            label: "ECIES blob".to_owned(),
+            expected: PUBKEY_LEN + NONCE_LEN,
+            actual: blob.len(),
+        });
+    }
+    let (ephemeral_pubkey_bytes, rest) = blob.split_at(PUBKEY_LEN);
+    let (nonce_bytes, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let ephemeral_pubkey =
+        PublicKey::from_sec1_bytes(ephemeral_pubkey_bytes).map_err(|e| CryptoError::InvalidPublicKey(e.to_string()))?;
+    let shared = diffie_hellman(&recipient_secret.to_nonzero_scalar(), ephemeral_pubkey.as_affine());
+    let key = derive_key(shared.raw_secret_bytes().as_slice());
+
+    let nonce = Nonce::from_slice(nonce_bytes);
+    let cipher = Aes256Gcm::new_from_slice(&key).map_err(|e| CryptoError::DecryptionFailed(e.to_string()))?;
+    cipher
+        .decrypt(nonce, ciphertext)
+        .map_err(|e| CryptoError::DecryptionFailed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{gen_prvkey, gen_pubkey};
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let prvkey = gen_prvkey();
+        let pubkey = gen_pubkey(&prvkey);
+
+        let blob = encrypt(&pubkey, b"top secret process spec").unwrap();
+        let plaintext = decrypt(&prvkey, &blob).unwrap();
+
+        assert_eq!(plaintext, b"top secret process spec");
+    }
+
+    #[test]
+    fn test_encrypt_is_not_deterministic() {
+        let prvkey = gen_prvkey();
+        let pubkey = gen_pubkey(&prvkey);
+
+        let blob1 = encrypt(&pubkey, b"hello").unwrap();
+        let blob2 = encrypt(&pubkey, b"hello").unwrap();
+
+        assert_ne!(blob1, blob2);
+    }
+
+    #[test]
+    fn test_decrypt_fails_for_wrong_recipient() {
+        let pubkey = gen_pubkey(&gen_prvkey());
+        let wrong_prvkey = gen_prvkey();
+
+        let blob = encrypt(&pubkey, b"hello").unwrap();
+        assert!(decrypt(&wrong_prvkey, &blob).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_fails_for_tampered_blob() {
+        let prvkey = gen_prvkey();
+        let pubkey = gen_pubkey(&prvkey);
+
+        let mut blob = encrypt(&pubkey, b"hello").unwrap();
+        let last = blob.len() - 1;
+        blob[last] ^= 0xff;
+
+        assert!(decrypt(&prvkey, &blob).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_rejects_truncated_blob() {
+        let prvkey = gen_prvkey();
+        assert!(decrypt(&prvkey, b"too short").is_err());
+    }
+
+    #[test]
+    fn test_encrypt_rejects_malformed_pubkey() {
+        assert!(encrypt("not-hex", b"hello").is_err());
+    }
+}
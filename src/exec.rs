@@ -0,0 +1,239 @@
+//! Generic OS-command execution handler.
+//!
+//! Every example hard-codes a fixed set of funcnames (`echo`, `add`,
+//! `sleep`, `calc_fibonacci`) as Rust closures. [`run`] instead treats
+//! `process.spec.args` as a command line, runs it with
+//! `tokio::process::Command`, and maps the result onto `set_output`/
+//! `close`/`fail` the same way [`crate::executor::Executor`]'s handlers do
+//! — so it can be registered directly as a handler to turn the crate into
+//! a remote command executor without writing Rust per function:
+//!
+//! ```rust,no_run
+//! use colonyos::exec::{self, ExecOptions};
+//! use colonyos::executor::ExecutorRuntime;
+//!
+//! # async fn run() {
+//! ExecutorRuntime::new("mycolony", "prvkey")
+//!     .register_handler("exec", |process| async move { exec::run(process, &ExecOptions::new()).await })
+//!     .run()
+//!     .await;
+//! # }
+//! ```
+
+use crate::core::Process;
+use crate::executor::ProcessError;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+
+/// Options controlling how [`run`] invokes `process.spec.args`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecOptions {
+    shell: bool,
+}
+
+impl ExecOptions {
+    pub fn new() -> ExecOptions {
+        ExecOptions::default()
+    }
+
+    /// Runs the command through a shell (`sh -c` on Unix, `cmd /C` on
+    /// Windows) instead of exec'ing `spec.args[0]` directly, so the spec
+    /// can use pipes, redirection, and globs.
+    pub fn shell(mut self, enabled: bool) -> ExecOptions {
+        self.shell = enabled;
+        self
+    }
+}
+
+/// Runs `process.spec.args` as a command line, enforcing `spec.maxexectime`
+/// (seconds) as a kill timeout and injecting `spec.env` into the child's
+/// environment. Stdout is captured line-by-line and returned as the output
+/// to pass to `set_output` on success; on a non-zero exit (or a timeout
+/// kill) the returned `ProcessError` carries the exit code or signal plus
+/// the captured stderr, so a caller can tell a timeout apart from a normal
+/// failing exit.
+pub async fn run(process: Process, opts: &ExecOptions) -> Result<Vec<String>, ProcessError> {
+    let spec = &process.spec;
+    if spec.args.is_empty() {
+        return Err(ProcessError::new("exec: spec.args is empty, nothing to run"));
+    }
+
+    let mut command = if opts.shell {
+        let mut command = shell_command();
+        command.arg(spec.args.join(" "));
+        command
+    } else {
+        let mut command = Command::new(&spec.args[0]);
+        command.args(&spec.args[1..]);
+        command
+    };
+
+    for (key, value) in &spec.env {
+        command.env(key, value);
+    }
+
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| ProcessError::new(&format!("exec: failed to start command: {e}")))?;
+
+    let stdout = child.stdout.take().expect("child spawned without piped stdout");
+    let stderr = child.stderr.take().expect("child spawned without piped stderr");
+    let stdout_task = tokio::spawn(collect_lines(stdout));
+    let stderr_task = tokio::spawn(collect_lines(stderr));
+
+    let status = if spec.maxexectime > 0 {
+        let timeout = Duration::from_secs(spec.maxexectime as u64);
+        match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(result) => result,
+            Err(_) => {
+                let _ = child.start_kill();
+                let _ = child.wait().await;
+                let _ = stdout_task.await;
+                let _ = stderr_task.await;
+                return Err(ProcessError::new(&format!(
+                    "exec: command timed out after {}s and was killed",
+                    timeout.as_secs()
+                )));
+            }
+        }
+    } else {
+        child.wait().await
+    };
+
+    let status = status.map_err(|e| ProcessError::new(&format!("exec: failed to wait for command: {e}")))?;
+    let stdout_lines = stdout_task.await.unwrap_or_default();
+    let stderr_lines = stderr_task.await.unwrap_or_default();
+
+    if status.success() {
+        Ok(stdout_lines)
+    } else {
+        Err(ProcessError::new(&format!("exec: {}", describe_failure(&status, &stderr_lines))))
+    }
+}
+
+async fn collect_lines(pipe: impl AsyncRead + Unpin) -> Vec<String> {
+    let mut lines = BufReader::new(pipe).lines();
+    let mut collected = Vec::new();
+    while let Ok(Some(line)) = lines.next_line().await {
+        collected.push(line);
+    }
+    collected
+}
+
+#[cfg(unix)]
+fn shell_command() -> Command {
+    let mut command = Command::new("sh");
+    command.arg("-c");
+    command
+}
+
+#[cfg(windows)]
+fn shell_command() -> Command {
+    let mut command = Command::new("cmd");
+    command.arg("/C");
+    command
+}
+
+#[cfg(unix)]
+fn describe_failure(status: &std::process::ExitStatus, stderr_lines: &[String]) -> String {
+    use std::os::unix::process::ExitStatusExt;
+    let detail = match (status.code(), status.signal()) {
+        (Some(code), _) => format!("exited with code {code}"),
+        (None, Some(signal)) => format!("killed by signal {signal}"),
+        (None, None) => "exited with an unknown status".to_owned(),
+    };
+    with_stderr(detail, stderr_lines)
+}
+
+#[cfg(not(unix))]
+fn describe_failure(status: &std::process::ExitStatus, stderr_lines: &[String]) -> String {
+    let detail = match status.code() {
+        Some(code) => format!("exited with code {code}"),
+        None => "exited with an unknown status".to_owned(),
+    };
+    with_stderr(detail, stderr_lines)
+}
+
+fn with_stderr(detail: String, stderr_lines: &[String]) -> String {
+    if stderr_lines.is_empty() {
+        detail
+    } else {
+        format!("{detail}: {}", stderr_lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::FunctionSpec;
+
+    fn process_with(args: Vec<&str>, maxexectime: i32) -> Process {
+        let mut spec = FunctionSpec::new("exec", "cli", "mycolony");
+        spec.args = args.into_iter().map(str::to_owned).collect();
+        spec.maxexectime = maxexectime;
+        Process {
+            processid: "process-123".to_owned(),
+            initiatorid: String::new(),
+            initiatorname: String::new(),
+            assignedexecutorid: String::new(),
+            isassigned: false,
+            state: crate::core::ProcessState::Waiting,
+            prioritytime: 0,
+            submissiontime: crate::core::colony_date_epoch(),
+            starttime: crate::core::colony_date_epoch(),
+            endtime: crate::core::colony_date_epoch(),
+            waitdeadline: crate::core::colony_date_epoch(),
+            execdeadline: crate::core::colony_date_epoch(),
+            retries: 0,
+            attributes: Vec::new(),
+            spec,
+            waitforparents: false,
+            parents: Vec::new(),
+            children: Vec::new(),
+            processgraphid: String::new(),
+            input: Vec::new(),
+            output: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_captures_stdout_on_success() {
+        let process = process_with(vec!["echo", "hello"], 0);
+        let output = run(process, &ExecOptions::new()).await.unwrap();
+        assert_eq!(output, vec!["hello".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn test_run_reports_exit_code_on_failure() {
+        let process = process_with(vec!["sh", "-c", "exit 7"], 0);
+        let err = run(process, &ExecOptions::new()).await.unwrap_err();
+        assert!(err.message.contains("exited with code 7"), "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn test_run_enforces_maxexectime_timeout() {
+        let process = process_with(vec!["sleep", "5"], 1);
+        let err = run(process, &ExecOptions::new()).await.unwrap_err();
+        assert!(err.message.contains("timed out"), "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn test_run_shell_mode_joins_args() {
+        let process = process_with(vec!["echo hi && echo there"], 0);
+        let output = run(process, &ExecOptions::new().shell(true)).await.unwrap();
+        assert_eq!(output, vec!["hi".to_owned(), "there".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn test_run_rejects_empty_args() {
+        let process = process_with(vec![], 0);
+        let err = run(process, &ExecOptions::new()).await.unwrap_err();
+        assert!(err.message.contains("spec.args is empty"));
+    }
+}
@@ -0,0 +1,740 @@
+//! Reusable executor runtime for native (tokio) ColonyOS executors.
+//!
+//! Every example up to now hand-rolls the assign -> dispatch -> close/fail
+//! loop and a giant `match process.spec.funcname` block. `ExecutorRuntime`,
+//! `Executor`, and `Runner` turn that boilerplate into a handler registry:
+//! register an async closure per function name, call `run()`, and the
+//! runtime takes care of assigning, dispatching, reporting results, and
+//! graceful shutdown. `ExecutorRuntime` handlers return raw output values
+//! (`set_output`); `Executor` handlers return `Attribute`s that are
+//! attached via `add_attr` before the process is closed; `Runner` handlers
+//! are Maelstrom-style, receiving a `Context` for incremental `log`/
+//! `set_output`/`close`/`fail` calls and an `on_init` hook run once before
+//! the loop starts. Across all three, a handler that panics is caught
+//! rather than silently dropping the tokio task, and fails the process
+//! with the panic message instead of leaving it stuck at `RUNNING`.
+
+use crate::backoff::BackoffPolicy;
+use crate::core::{Attribute, Log, Process};
+use crate::rpc::RPCError;
+use futures_util::FutureExt;
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+
+/// Error returned by a registered handler.
+#[derive(Debug, Clone)]
+pub struct ProcessError {
+    pub message: String,
+}
+
+impl ProcessError {
+    pub fn new(message: &str) -> ProcessError {
+        ProcessError {
+            message: message.to_owned(),
+        }
+    }
+}
+
+impl std::fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Turns a caught `std::panic::catch_unwind` payload into a `ProcessError`,
+/// so a handler that panics fails its process with a readable message
+/// instead of leaving it stuck at `RUNNING` forever (the panic otherwise
+/// only surfaces as a silently-dropped tokio task).
+fn panic_to_process_error(panic: Box<dyn std::any::Any + Send>) -> ProcessError {
+    let message = if let Some(s) = panic.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = panic.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "handler panicked".to_string()
+    };
+    ProcessError::new(&format!("handler panicked: {message}"))
+}
+
+type HandlerResult = Pin<Box<dyn Future<Output = Result<Vec<String>, ProcessError>> + Send>>;
+type Handler = Arc<dyn Fn(Process) -> HandlerResult + Send + Sync>;
+
+/// Builder for a long-running native executor.
+///
+/// # Example
+/// ```rust,no_run
+/// use colonyos::executor::ExecutorRuntime;
+///
+/// # async fn run() {
+/// let runtime = ExecutorRuntime::new("mycolony", "prvkey")
+///     .concurrency(8)
+///     .register_handler("echo", |process| async move {
+///         Ok(process.spec.args)
+///     });
+///
+/// runtime.run().await;
+/// # }
+/// ```
+pub struct ExecutorRuntime {
+    colonyname: String,
+    prvkey: String,
+    assign_timeout: i32,
+    concurrency: usize,
+    grace_period: Duration,
+    backoff: BackoffPolicy,
+    handlers: HashMap<String, Handler>,
+    shutdown: CancellationToken,
+}
+
+impl ExecutorRuntime {
+    pub fn new(colonyname: &str, prvkey: &str) -> ExecutorRuntime {
+        ExecutorRuntime {
+            colonyname: colonyname.to_owned(),
+            prvkey: prvkey.to_owned(),
+            assign_timeout: 10,
+            concurrency: 4,
+            grace_period: Duration::from_secs(30),
+            backoff: crate::backoff::default_policy(),
+            handlers: HashMap::new(),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    /// Sets the backoff policy applied between `assign` retries after a
+    /// connection failure. Timeouts (no process currently available) are
+    /// unaffected and retry immediately, as before.
+    pub fn backoff(mut self, backoff: BackoffPolicy) -> ExecutorRuntime {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Sets the long-poll timeout (seconds) used for each `assign` call.
+    pub fn assign_timeout(mut self, seconds: i32) -> ExecutorRuntime {
+        self.assign_timeout = seconds;
+        self
+    }
+
+    /// Bounds the number of handlers that may run concurrently.
+    pub fn concurrency(mut self, limit: usize) -> ExecutorRuntime {
+        self.concurrency = limit;
+        self
+    }
+
+    /// Bounds how long `run()` waits for in-flight handlers to finish after
+    /// a shutdown signal before returning anyway.
+    pub fn grace_period(mut self, grace: Duration) -> ExecutorRuntime {
+        self.grace_period = grace;
+        self
+    }
+
+    /// Returns a handle that can be used to trigger a cooperative shutdown
+    /// from outside `run()`, e.g. in response to an application-level
+    /// condition rather than a process signal.
+    pub fn shutdown_handle(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Registers an async handler for a function name. The closure receives
+    /// the assigned `Process` and must resolve to the process output on
+    /// success, or a `ProcessError` describing why it failed.
+    pub fn register_handler<F, Fut>(mut self, funcname: &str, handler: F) -> ExecutorRuntime
+    where
+        F: Fn(Process) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<String>, ProcessError>> + Send + 'static,
+    {
+        self.handlers
+            .insert(funcname.to_owned(), Arc::new(move |p| Box::pin(handler(p))));
+        self
+    }
+
+    /// Drives the assign -> dispatch -> close/fail loop until a shutdown
+    /// signal (SIGINT/SIGTERM) or the `shutdown_handle()` token fires, then
+    /// waits up to `grace_period` for in-flight handlers to finish before
+    /// returning. No new assignments are requested once shutdown begins.
+    pub async fn run(self) {
+        let semaphore = Arc::new(Semaphore::new(self.concurrency.max(1)));
+        let handlers = Arc::new(self.handlers);
+        let colonyname = self.colonyname.clone();
+        let prvkey = Arc::new(self.prvkey.clone());
+        let concurrency = self.concurrency.max(1);
+        let mut attempt: u32 = 0;
+
+        loop {
+            tokio::select! {
+                _ = shutdown_signal() => {
+                    break;
+                }
+                _ = self.shutdown.cancelled() => {
+                    break;
+                }
+                res = crate::assign(&colonyname, self.assign_timeout, &prvkey) => {
+                    match res {
+                        Ok(process) => {
+                            attempt = 0;
+                            let permit = semaphore.clone().acquire_owned().await.unwrap();
+                            let handlers = handlers.clone();
+                            let prvkey = prvkey.clone();
+                            crate::rt::spawn(async move {
+                                dispatch(process, handlers, prvkey).await;
+                                drop(permit);
+                            });
+                        }
+                        Err(e) => {
+                            if e.conn_err() {
+                                if !self.backoff.should_retry(attempt) {
+                                    break;
+                                }
+                                crate::rt::sleep(self.backoff.delay(attempt)).await;
+                                attempt += 1;
+                            }
+                            // Non-connection errors (e.g. assign timeout
+                            // because no process was available) retry
+                            // immediately, same as before.
+                        }
+                    }
+                }
+            }
+        }
+
+        // Drain in-flight handlers, but don't wait past the grace period;
+        // any handler still running at that point keeps going in the
+        // background, same tradeoff as the Lua script timeout.
+        let _ = crate::rt::timeout(
+            self.grace_period,
+            semaphore.acquire_many(concurrency as u32),
+        )
+        .await;
+    }
+}
+
+type AttrHandlerResult = Pin<Box<dyn Future<Output = Result<Vec<Attribute>, ProcessError>> + Send>>;
+type AttrHandler = Arc<dyn Fn(Process) -> AttrHandlerResult + Send + Sync>;
+
+/// Builder for a long-running native executor whose handlers report results
+/// as process attributes rather than raw output values.
+///
+/// # Example
+/// ```rust,no_run
+/// use colonyos::executor::Executor;
+///
+/// # async fn run() {
+/// Executor::new("mycolony", "prvkey")
+///     .handler("say", |process| async move {
+///         Ok(vec![])
+///     })
+///     .run()
+///     .await;
+/// # }
+/// ```
+pub struct Executor {
+    colonyname: String,
+    prvkey: String,
+    assign_timeout: i32,
+    concurrency: usize,
+    handlers: HashMap<String, AttrHandler>,
+}
+
+impl Executor {
+    pub fn new(colonyname: &str, prvkey: &str) -> Executor {
+        Executor {
+            colonyname: colonyname.to_owned(),
+            prvkey: prvkey.to_owned(),
+            assign_timeout: 10,
+            concurrency: 4,
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Sets the long-poll timeout (seconds) used for each `assign` call.
+    pub fn assign_timeout(mut self, seconds: i32) -> Executor {
+        self.assign_timeout = seconds;
+        self
+    }
+
+    /// Bounds the number of handlers that may run concurrently.
+    pub fn concurrency(mut self, limit: usize) -> Executor {
+        self.concurrency = limit;
+        self
+    }
+
+    /// Registers an async handler for a function name. The closure receives
+    /// the assigned `Process` and must resolve to the attributes to attach
+    /// on success, or a `ProcessError` describing why it failed.
+    pub fn handler<F, Fut>(mut self, funcname: &str, handler: F) -> Executor
+    where
+        F: Fn(Process) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<Attribute>, ProcessError>> + Send + 'static,
+    {
+        self.handlers
+            .insert(funcname.to_owned(), Arc::new(move |p| Box::pin(handler(p))));
+        self
+    }
+
+    /// Drives the assign -> dispatch -> close/fail loop until a shutdown
+    /// signal (SIGINT/SIGTERM) is received, then waits for in-flight
+    /// handlers to finish before returning.
+    pub async fn run(self) {
+        let semaphore = Arc::new(Semaphore::new(self.concurrency.max(1)));
+        let handlers = Arc::new(self.handlers);
+        let colonyname = self.colonyname.clone();
+        let prvkey = Arc::new(self.prvkey.clone());
+
+        let mut shutdown = Box::pin(shutdown_signal());
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => {
+                    break;
+                }
+                res = crate::assign(&colonyname, self.assign_timeout, &prvkey) => {
+                    match res {
+                        Ok(process) => {
+                            let permit = semaphore.clone().acquire_owned().await.unwrap();
+                            let handlers = handlers.clone();
+                            let prvkey = prvkey.clone();
+                            crate::rt::spawn(async move {
+                                dispatch_attrs(process, handlers, prvkey).await;
+                                drop(permit);
+                            });
+                        }
+                        Err(e) => {
+                            if e.conn_err() {
+                                crate::rt::sleep(Duration::from_secs(1)).await;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Drain in-flight handlers before returning.
+        let _ = semaphore.acquire_many(self.concurrency.max(1) as u32).await;
+    }
+}
+
+async fn dispatch_attrs(process: Process, handlers: Arc<HashMap<String, AttrHandler>>, prvkey: Arc<String>) {
+    let processid = process.processid.clone();
+    let colonyname = process.colonyname.clone();
+    let funcname = process.spec.funcname.clone();
+
+    let handler = match handlers.get(&funcname) {
+        Some(h) => h.clone(),
+        None => {
+            let _ = crate::fail_with(&processid, &format!("unknown function: {funcname}"), &prvkey).await;
+            return;
+        }
+    };
+
+    let result = AssertUnwindSafe(handler(process))
+        .catch_unwind()
+        .await
+        .unwrap_or_else(|panic| Err(panic_to_process_error(panic)));
+
+    match result {
+        Ok(attrs) => {
+            for attr in attrs {
+                let attr = Attribute::new(&colonyname, &processid, &attr.key, &attr.value);
+                let _: Result<Attribute, RPCError> = crate::add_attr(&attr, &prvkey).await;
+            }
+            let _ = crate::close(&processid, &prvkey).await;
+        }
+        Err(e) => {
+            let _ = crate::fail_with(&processid, &e.message, &prvkey).await;
+        }
+    }
+}
+
+async fn dispatch(process: Process, handlers: Arc<HashMap<String, Handler>>, prvkey: Arc<String>) {
+    let processid = process.processid.clone();
+    let funcname = process.spec.funcname.clone();
+
+    let handler = match handlers.get(&funcname) {
+        Some(h) => h.clone(),
+        None => {
+            let _ = crate::fail_with(&processid, &format!("unknown function: {funcname}"), &prvkey).await;
+            return;
+        }
+    };
+
+    let result = AssertUnwindSafe(handler(process))
+        .catch_unwind()
+        .await
+        .unwrap_or_else(|panic| Err(panic_to_process_error(panic)));
+
+    match result {
+        Ok(output) => {
+            let _: Result<(), RPCError> = crate::close_with_output(&processid, output, &prvkey).await;
+        }
+        Err(e) => {
+            let _ = crate::fail_with(&processid, &e.message, &prvkey).await;
+        }
+    }
+}
+
+/// Passed to every [`Runner`] handler. Mirrors the context object handed to
+/// a Maelstrom `Node` handler: a way to emit log lines and partial output
+/// while the handler runs, plus read-only access to the process's current
+/// attributes, without the handler needing to know the processid/prvkey.
+pub struct Context {
+    colonyname: String,
+    processid: String,
+    executorname: String,
+    prvkey: Arc<String>,
+    attributes: Vec<Attribute>,
+}
+
+impl Context {
+    /// The attributes already attached to the process at assign time.
+    pub fn attributes(&self) -> &[Attribute] {
+        &self.attributes
+    }
+
+    /// Appends a log line to the process's log, visible to watchers before
+    /// the process closes.
+    pub async fn log(&self, msg: &str) {
+        let log = Log {
+            processid: self.processid.clone(),
+            colonyname: self.colonyname.clone(),
+            executorname: self.executorname.clone(),
+            message: msg.to_owned(),
+            timestamp: "0".to_string(),
+        };
+        let _ = crate::add_log(&log, &self.prvkey).await;
+    }
+
+    /// Sets the process's output ahead of the final close, e.g. to report
+    /// partial progress on a long-running handler.
+    pub async fn set_output(&self, output: Vec<String>) {
+        let _ = crate::set_output(&self.processid, output, &self.prvkey).await;
+    }
+
+    /// Closes the process directly, for a handler that wants to report
+    /// success itself instead of returning `Ok` and letting the runtime
+    /// close it. `run()` still closes the process on an `Ok` return, so a
+    /// handler that calls this should return `Ok(vec![])` afterwards to
+    /// avoid a redundant close.
+    pub async fn close(&self) {
+        let _ = crate::close(&self.processid, &self.prvkey).await;
+    }
+
+    /// Fails the process directly with `message`, for a handler that wants
+    /// to report failure itself instead of returning `Err`.
+    pub async fn fail(&self, message: &str) {
+        let _ = crate::fail_with(&self.processid, message, &self.prvkey).await;
+    }
+}
+
+type RunnerHandlerResult = Pin<Box<dyn Future<Output = Result<Vec<String>, ProcessError>> + Send>>;
+type RunnerHandler = Arc<dyn Fn(Context, Process) -> RunnerHandlerResult + Send + Sync>;
+
+/// Maelstrom-style `Runner`/`Node` executor: a registry mapping function
+/// names to handlers that receive a [`Context`] alongside the assigned
+/// `Process`, and an `on_init` hook run once before the assign loop starts
+/// so callers can spawn background tasks the way Maelstrom examples do.
+///
+/// # Example
+/// ```rust,no_run
+/// use colonyos::executor::Runner;
+///
+/// # async fn run() {
+/// Runner::new("mycolony", "worker-1", "prvkey")
+///     .register_handler("echo", |ctx, process| async move {
+///         ctx.log("starting").await;
+///         Ok(process.spec.args)
+///     })
+///     .run(|| async {})
+///     .await;
+/// # }
+/// ```
+pub struct Runner {
+    colonyname: String,
+    executorname: String,
+    prvkey: String,
+    assign_timeout: i32,
+    handlers: HashMap<String, RunnerHandler>,
+}
+
+impl Runner {
+    pub fn new(colonyname: &str, executorname: &str, prvkey: &str) -> Runner {
+        Runner {
+            colonyname: colonyname.to_owned(),
+            executorname: executorname.to_owned(),
+            prvkey: prvkey.to_owned(),
+            assign_timeout: 10,
+            handlers: HashMap::new(),
+        }
+    }
+
+    /// Sets the long-poll timeout (seconds) used for each `assign` call.
+    pub fn assign_timeout(mut self, seconds: i32) -> Runner {
+        self.assign_timeout = seconds;
+        self
+    }
+
+    /// Registers an async handler for a function name. The closure receives
+    /// a [`Context`] and the assigned `Process`, and must resolve to the
+    /// process output on success, or a `ProcessError` describing why it
+    /// failed.
+    pub fn register_handler<F, Fut>(mut self, funcname: &str, handler: F) -> Runner
+    where
+        F: Fn(Context, Process) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Vec<String>, ProcessError>> + Send + 'static,
+    {
+        self.handlers
+            .insert(funcname.to_owned(), Arc::new(move |ctx, p| Box::pin(handler(ctx, p))));
+        self
+    }
+
+    /// Runs `on_init` once, then drives the assign -> dispatch -> close/fail
+    /// loop forever, re-looping immediately whenever `assign` times out
+    /// (i.e. no process was available), until a shutdown signal
+    /// (SIGINT/SIGTERM) is received.
+    pub async fn run<F, Fut>(self, on_init: F)
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = ()>,
+    {
+        on_init().await;
+
+        let handlers = Arc::new(self.handlers);
+        let colonyname = self.colonyname.clone();
+        let executorname = self.executorname.clone();
+        let prvkey = Arc::new(self.prvkey.clone());
+
+        let mut shutdown = Box::pin(shutdown_signal());
+
+        loop {
+            tokio::select! {
+                _ = &mut shutdown => {
+                    break;
+                }
+                res = crate::assign(&colonyname, self.assign_timeout, &prvkey) => {
+                    match res {
+                        Ok(process) => {
+                            dispatch_with_context(process, &handlers, &executorname, &prvkey).await;
+                        }
+                        Err(_) => {
+                            // No process available (or a transient error) -
+                            // simply re-loop, as the Maelstrom examples do.
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+async fn dispatch_with_context(
+    process: Process,
+    handlers: &HashMap<String, RunnerHandler>,
+    executorname: &str,
+    prvkey: &Arc<String>,
+) {
+    let processid = process.processid.clone();
+    let funcname = process.spec.funcname.clone();
+
+    let handler = match handlers.get(&funcname) {
+        Some(h) => h.clone(),
+        None => {
+            let _ = crate::fail_with(&processid, &format!("unknown function: {funcname}"), prvkey).await;
+            return;
+        }
+    };
+
+    let ctx = Context {
+        colonyname: process.spec.conditions.colonyname.clone(),
+        processid: processid.clone(),
+        executorname: executorname.to_owned(),
+        prvkey: prvkey.clone(),
+        attributes: process.attributes.clone(),
+    };
+
+    let result = AssertUnwindSafe(handler(ctx, process))
+        .catch_unwind()
+        .await
+        .unwrap_or_else(|panic| Err(panic_to_process_error(panic)));
+
+    match result {
+        Ok(output) => {
+            let _: Result<(), RPCError> = crate::close_with_output(&processid, output, prvkey).await;
+        }
+        Err(e) => {
+            let _ = crate::fail_with(&processid, &e.message, prvkey).await;
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn shutdown_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+    let mut sigint = signal(SignalKind::interrupt()).expect("failed to install SIGINT handler");
+    let mut sigterm = signal(SignalKind::terminate()).expect("failed to install SIGTERM handler");
+    tokio::select! {
+        _ = sigint.recv() => {}
+        _ = sigterm.recv() => {}
+    }
+}
+
+#[cfg(not(unix))]
+async fn shutdown_signal() {
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_error_display() {
+        let err = ProcessError::new("boom");
+        assert_eq!(err.message, "boom");
+        assert_eq!(format!("{}", err), "boom");
+    }
+
+    #[test]
+    fn test_builder_defaults() {
+        let runtime = ExecutorRuntime::new("mycolony", "prvkey");
+        assert_eq!(runtime.colonyname, "mycolony");
+        assert_eq!(runtime.assign_timeout, 10);
+        assert_eq!(runtime.concurrency, 4);
+    }
+
+    #[test]
+    fn test_builder_overrides() {
+        let runtime = ExecutorRuntime::new("mycolony", "prvkey")
+            .assign_timeout(30)
+            .concurrency(16);
+        assert_eq!(runtime.assign_timeout, 30);
+        assert_eq!(runtime.concurrency, 16);
+    }
+
+    #[test]
+    fn test_register_handler() {
+        let runtime = ExecutorRuntime::new("mycolony", "prvkey")
+            .register_handler("echo", |p| async move { Ok(p.spec.args) });
+        assert!(runtime.handlers.contains_key("echo"));
+    }
+
+    #[test]
+    fn test_backoff_override() {
+        let policy = BackoffPolicy::new().max_retries(5);
+        let runtime = ExecutorRuntime::new("mycolony", "prvkey").backoff(policy);
+        assert!(runtime.backoff.should_retry(4));
+        assert!(!runtime.backoff.should_retry(5));
+    }
+
+    #[test]
+    fn test_grace_period_override() {
+        let runtime = ExecutorRuntime::new("mycolony", "prvkey").grace_period(Duration::from_secs(5));
+        assert_eq!(runtime.grace_period, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_shutdown_handle_triggers_token() {
+        let runtime = ExecutorRuntime::new("mycolony", "prvkey");
+        let handle = runtime.shutdown_handle();
+        assert!(!handle.is_cancelled());
+        handle.cancel();
+        assert!(runtime.shutdown.is_cancelled());
+    }
+
+    #[test]
+    fn test_executor_builder_defaults() {
+        let executor = Executor::new("mycolony", "prvkey");
+        assert_eq!(executor.colonyname, "mycolony");
+        assert_eq!(executor.assign_timeout, 10);
+        assert_eq!(executor.concurrency, 4);
+    }
+
+    #[test]
+    fn test_executor_handler_registration() {
+        let executor = Executor::new("mycolony", "prvkey")
+            .handler("say", |_process| async move { Ok(vec![]) });
+        assert!(executor.handlers.contains_key("say"));
+    }
+
+    #[test]
+    fn test_runner_builder_defaults() {
+        let runner = Runner::new("mycolony", "worker-1", "prvkey");
+        assert_eq!(runner.colonyname, "mycolony");
+        assert_eq!(runner.executorname, "worker-1");
+        assert_eq!(runner.assign_timeout, 10);
+    }
+
+    #[test]
+    fn test_runner_handler_registration() {
+        let runner = Runner::new("mycolony", "worker-1", "prvkey")
+            .register_handler("echo", |_ctx, process| async move { Ok(process.spec.args) });
+        assert!(runner.handlers.contains_key("echo"));
+    }
+
+    #[test]
+    fn test_panic_to_process_error_str_payload() {
+        let panic = std::panic::catch_unwind(|| panic!("boom")).unwrap_err();
+        let err = panic_to_process_error(panic);
+        assert_eq!(err.message, "handler panicked: boom");
+    }
+
+    #[test]
+    fn test_panic_to_process_error_string_payload() {
+        let panic = std::panic::catch_unwind(|| panic!("{}", "boom".to_string())).unwrap_err();
+        let err = panic_to_process_error(panic);
+        assert_eq!(err.message, "handler panicked: boom");
+    }
+
+    fn process_with(funcname: &str) -> Process {
+        let spec = crate::core::FunctionSpec::new(funcname, "test-executor", "mycolony");
+        Process {
+            processid: "process-123".to_owned(),
+            initiatorid: String::new(),
+            initiatorname: String::new(),
+            assignedexecutorid: String::new(),
+            isassigned: false,
+            state: crate::core::ProcessState::Waiting,
+            prioritytime: 0,
+            submissiontime: crate::core::colony_date_epoch(),
+            starttime: crate::core::colony_date_epoch(),
+            endtime: crate::core::colony_date_epoch(),
+            waitdeadline: crate::core::colony_date_epoch(),
+            execdeadline: crate::core::colony_date_epoch(),
+            retries: 0,
+            attributes: Vec::new(),
+            spec,
+            waitforparents: false,
+            parents: Vec::new(),
+            children: Vec::new(),
+            processgraphid: String::new(),
+            input: Vec::new(),
+            output: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_catches_handler_panic() {
+        let mut handlers: HashMap<String, Handler> = HashMap::new();
+        handlers.insert(
+            "boom".to_owned(),
+            Arc::new(|_process| Box::pin(async move { panic!("handler exploded") })),
+        );
+        let handlers = Arc::new(handlers);
+        let process = process_with("boom");
+
+        // dispatch() talks to a real server to close/fail the process, which
+        // isn't available here; the assertion that matters is that calling
+        // the panicking handler through catch_unwind doesn't unwind out of
+        // this test.
+        let handler = handlers.get("boom").unwrap().clone();
+        let result: Result<Vec<String>, ProcessError> = AssertUnwindSafe(handler(process))
+            .catch_unwind()
+            .await
+            .unwrap_or_else(|panic| Err(panic_to_process_error(panic)));
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,152 @@
+//! Content-addressed directory sync for process inputs and outputs.
+//!
+//! `output: Vec<String>` and attributes are fine for small results, but
+//! real workflows move whole directories of files between the submitter
+//! and an assigned executor. `sync_up`/`sync_down` walk a local directory,
+//! hash each file with `crypto::gen_hash`, and only transfer files whose
+//! checksum differs from the manifest the server already holds under a
+//! given label, so repeated runs don't re-upload unchanged large files.
+
+use crate::crypto;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// One file's identity within a sync label: its relative path and content hash.
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub relpath: String,
+    pub checksum: String,
+}
+
+/// The set of files known to exist under a label, keyed by relative path.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Manifest {
+    pub label: String,
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl Manifest {
+    fn index(&self) -> HashMap<&str, &str> {
+        self.entries
+            .iter()
+            .map(|e| (e.relpath.as_str(), e.checksum.as_str()))
+            .collect()
+    }
+}
+
+/// Walks `local_dir` and computes a manifest without touching the network.
+pub fn compute_manifest(local_dir: &Path, label: &str) -> std::io::Result<Manifest> {
+    let mut entries = Vec::new();
+    walk(local_dir, local_dir, &mut entries)?;
+    entries.sort_by(|a, b| a.relpath.cmp(&b.relpath));
+    Ok(Manifest {
+        label: label.to_owned(),
+        entries,
+    })
+}
+
+fn walk(root: &Path, dir: &Path, out: &mut Vec<ManifestEntry>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            walk(root, &path, out)?;
+        } else {
+            let bytes = std::fs::read(&path)?;
+            let checksum = crypto::gen_hash(&String::from_utf8_lossy(&bytes));
+            let relpath = path
+                .strip_prefix(root)
+                .unwrap_or(&path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            out.push(ManifestEntry { relpath, checksum });
+        }
+    }
+    Ok(())
+}
+
+/// Diffs a freshly computed local manifest against the manifest the server
+/// reports holding for `label`, returning only the relative paths that need
+/// to be (re-)uploaded.
+pub fn diff_for_upload(local: &Manifest, remote: &Manifest) -> Vec<String> {
+    let remote_index = remote.index();
+    local
+        .entries
+        .iter()
+        .filter(|e| remote_index.get(e.relpath.as_str()) != Some(&e.checksum.as_str()))
+        .map(|e| e.relpath.clone())
+        .collect()
+}
+
+/// Determines which files in `local_dir` need to be (re-)uploaded given the
+/// manifest the server already holds under `label`.
+///
+/// This only computes the upload set; the actual object transport is left
+/// to the caller's artifact API (see [`crate::artifact`]).
+pub fn sync_up(local_dir: &Path, label: &str, remote: &Manifest) -> std::io::Result<Vec<PathBuf>> {
+    let manifest = compute_manifest(local_dir, label)?;
+    let changed = diff_for_upload(&manifest, remote);
+    Ok(changed.into_iter().map(|rel| local_dir.join(rel)).collect())
+}
+
+/// The inverse of [`sync_up`]: given the server's manifest for `label`,
+/// returns the relative paths the caller should fetch and materialize under
+/// `local_dir` because they are missing or stale locally.
+pub fn diff_for_download(remote: &Manifest, local_dir: &Path) -> std::io::Result<Vec<String>> {
+    let local = compute_manifest(local_dir, &remote.label)?;
+    let local_index = local.index();
+    Ok(remote
+        .entries
+        .iter()
+        .filter(|e| local_index.get(e.relpath.as_str()) != Some(&e.checksum.as_str()))
+        .map(|e| e.relpath.clone())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_compute_manifest_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("colonyos_fs_test_{}", crypto::gen_prvkey()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), b"hello").unwrap();
+        fs::write(dir.join("sub/b.txt"), b"world").unwrap();
+
+        let manifest = compute_manifest(&dir, "mylabel").unwrap();
+        assert_eq!(manifest.label, "mylabel");
+        assert_eq!(manifest.entries.len(), 2);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_diff_for_upload_only_changed() {
+        let local = Manifest {
+            label: "l".to_owned(),
+            entries: vec![
+                ManifestEntry { relpath: "a".into(), checksum: "111".into() },
+                ManifestEntry { relpath: "b".into(), checksum: "222".into() },
+            ],
+        };
+        let remote = Manifest {
+            label: "l".to_owned(),
+            entries: vec![ManifestEntry { relpath: "a".into(), checksum: "111".into() }],
+        };
+
+        let changed = diff_for_upload(&local, &remote);
+        assert_eq!(changed, vec!["b".to_string()]);
+    }
+
+    #[test]
+    fn test_diff_for_upload_no_changes() {
+        let m = Manifest {
+            label: "l".to_owned(),
+            entries: vec![ManifestEntry { relpath: "a".into(), checksum: "111".into() }],
+        };
+        assert!(diff_for_upload(&m, &m).is_empty());
+    }
+}
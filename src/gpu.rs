@@ -0,0 +1,162 @@
+//! PCI-based GPU discovery to fill in `GPU` capabilities.
+//!
+//! `GPU::default()` makes GPU reporting all-or-nothing today. This walks
+//! `/sys/bus/pci/devices/*/{vendor,class}` looking for display/3D
+//! controllers from known GPU vendors, counts them into `GPU.count`, and,
+//! when the NVIDIA stack is present, shells out to `nvidia-smi` to fill in
+//! memory and model name. The PCI-scan root is injectable so tests can
+//! point it at a fixture tree instead of the real `/sys`.
+
+use crate::core::GPU;
+use std::path::Path;
+use std::process::Command;
+
+const NVIDIA_VENDOR_ID: &str = "0x10de";
+const AMD_VENDOR_ID: &str = "0x1002";
+const DISPLAY_CONTROLLER_CLASS: &str = "0x0300";
+const THREE_D_CONTROLLER_CLASS: &str = "0x0302";
+
+/// Enumerates PCI devices under `pci_root` (normally `/sys/bus/pci/devices`)
+/// and returns detected GPU capabilities, falling back to `GPU::default()`
+/// when nothing is found. When an NVIDIA device is detected, `nvidia-smi`
+/// is queried to fill in `mem`/`name`; a missing or failing `nvidia-smi`
+/// just leaves those fields blank rather than failing detection.
+pub fn discover_gpu(pci_root: &Path) -> GPU {
+    let devices = match std::fs::read_dir(pci_root) {
+        Ok(entries) => entries,
+        Err(_) => return GPU::default(),
+    };
+
+    let mut count = 0;
+    let mut has_nvidia = false;
+    let mut has_amd = false;
+
+    for entry in devices.flatten() {
+        let path = entry.path();
+        let vendor = read_trimmed(&path.join("vendor"));
+        let class = read_trimmed(&path.join("class"));
+
+        let (Some(vendor), Some(class)) = (vendor, class) else {
+            continue;
+        };
+
+        if !is_display_class(&class) {
+            continue;
+        }
+
+        match vendor.as_str() {
+            NVIDIA_VENDOR_ID => {
+                has_nvidia = true;
+                count += 1;
+            }
+            AMD_VENDOR_ID => {
+                has_amd = true;
+                count += 1;
+            }
+            _ => {}
+        }
+    }
+
+    if count == 0 {
+        return GPU::default();
+    }
+
+    let mut gpu = GPU {
+        count,
+        ..GPU::default()
+    };
+
+    if has_nvidia {
+        if let Some((name, mem)) = query_nvidia_smi() {
+            gpu.name = name;
+            gpu.mem = mem;
+        }
+    } else if has_amd {
+        gpu.name = "AMD GPU".to_owned();
+    }
+
+    gpu
+}
+
+fn is_display_class(class: &str) -> bool {
+    // PCI class codes carry sub-class/prog-if in their lower bits; compare
+    // only the base class + sub-class (top 6 hex digits including the 0x).
+    let prefix_len = "0x0300".len();
+    class.len() >= prefix_len
+        && (class[..prefix_len] == *DISPLAY_CONTROLLER_CLASS || class[..prefix_len] == *THREE_D_CONTROLLER_CLASS)
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    std::fs::read_to_string(path).ok().map(|s| s.trim().to_lowercase())
+}
+
+fn query_nvidia_smi() -> Option<(String, String)> {
+    let output = Command::new("nvidia-smi")
+        .args(["--query-gpu=memory.total,name", "--format=csv,noheader"])
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let text = String::from_utf8_lossy(&output.stdout);
+    let first_line = text.lines().next()?;
+    let mut parts = first_line.splitn(2, ',');
+    let mem = parts.next()?.trim().to_owned();
+    let name = parts.next()?.trim().to_owned();
+    Some((name, mem))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write_pci_device(root: &Path, name: &str, vendor: &str, class: &str) {
+        let dir = root.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("vendor"), vendor).unwrap();
+        fs::write(dir.join("class"), class).unwrap();
+    }
+
+    #[test]
+    fn test_discover_gpu_no_devices_returns_default() {
+        let root = std::env::temp_dir().join("colonyos-gpu-test-empty");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+
+        let gpu = discover_gpu(&root);
+        assert_eq!(gpu.count, 0);
+        assert_eq!(gpu.name, "");
+    }
+
+    #[test]
+    fn test_discover_gpu_counts_nvidia_device() {
+        let root = std::env::temp_dir().join("colonyos-gpu-test-nvidia");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        write_pci_device(&root, "0000:01:00.0", "0x10de", "0x030000");
+
+        let gpu = discover_gpu(&root);
+        assert_eq!(gpu.count, 1);
+    }
+
+    #[test]
+    fn test_discover_gpu_ignores_non_display_devices() {
+        let root = std::env::temp_dir().join("colonyos-gpu-test-non-display");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        write_pci_device(&root, "0000:00:1f.0", "0x8086", "0x060100");
+
+        let gpu = discover_gpu(&root);
+        assert_eq!(gpu.count, 0);
+    }
+
+    #[test]
+    fn test_is_display_class() {
+        assert!(is_display_class("0x030000"));
+        assert!(is_display_class("0x030200"));
+        assert!(!is_display_class("0x060100"));
+    }
+}
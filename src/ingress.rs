@@ -0,0 +1,196 @@
+//! HMAC-authenticated webhook bridge that maps external events to process
+//! submissions.
+//!
+//! Lets external systems (CI hooks, cron callbacks, IoT triggers) launch
+//! colony processes over a plain HTTP endpoint without embedding colony
+//! private keys in every caller. Each configured source carries a
+//! pre-shared key; requests are authenticated by comparing an HMAC-SHA256
+//! over the raw body against a signature header before any parsing
+//! happens.
+
+use crate::core::FunctionSpec;
+use axum::body::Bytes;
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A single configured inbound source: a name, a pre-shared HMAC key, and a
+/// `FunctionSpec` template with `${field}` placeholders filled in from the
+/// JSON body.
+#[derive(Clone)]
+pub struct Source {
+    pub name: String,
+    pub shared_key: Vec<u8>,
+    pub template: FunctionSpec,
+    pub prvkey: String,
+}
+
+#[derive(Clone)]
+struct IngressState {
+    sources: Arc<HashMap<String, Source>>,
+}
+
+/// Builds the axum router. Mount it under whatever prefix the deployment
+/// wants; each source is reachable at `/webhook/:name`.
+pub fn router(sources: Vec<Source>) -> Router {
+    let map = sources.into_iter().map(|s| (s.name.clone(), s)).collect();
+    let state = IngressState {
+        sources: Arc::new(map),
+    };
+    Router::new()
+        .route("/webhook/:name", post(handle_webhook))
+        .with_state(state)
+}
+
+async fn handle_webhook(
+    State(state): State<IngressState>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let source = match state.sources.get(&name) {
+        Some(s) => s,
+        None => return (StatusCode::NOT_FOUND, "unknown source").into_response(),
+    };
+
+    let signature = match headers.get("x-signature").and_then(|v| v.to_str().ok()) {
+        Some(s) => s,
+        None => return (StatusCode::UNAUTHORIZED, "missing signature").into_response(),
+    };
+
+    if !verify_signature(&source.shared_key, &body, signature) {
+        return (StatusCode::UNAUTHORIZED, "signature mismatch").into_response();
+    }
+
+    let payload: Value = match serde_json::from_slice(&body) {
+        Ok(v) => v,
+        Err(e) => return (StatusCode::BAD_REQUEST, format!("invalid json: {e}")).into_response(),
+    };
+
+    let spec = substitute_template(&source.template, &payload);
+
+    match crate::submit(&spec, &source.prvkey).await {
+        Ok(process) => Json(serde_json::json!({ "processid": process.processid })).into_response(),
+        Err(e) => (StatusCode::BAD_GATEWAY, e.to_string()).into_response(),
+    }
+}
+
+/// Computes HMAC-SHA256 over `body` with `key` and constant-time compares it
+/// against a hex-encoded `signature` (with an optional `sha256=` prefix, as
+/// used by GitHub-style webhooks).
+pub fn verify_signature(key: &[u8], body: &[u8], signature: &str) -> bool {
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+    let expected = match hex::decode(signature) {
+        Ok(bytes) => bytes,
+        Err(_) => return false,
+    };
+
+    let mut mac = match HmacSha256::new_from_slice(key) {
+        Ok(m) => m,
+        Err(_) => return false,
+    };
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Fills `${field}` placeholders in a template's `funcname`/`args`/`kwargs`
+/// from top-level fields of the JSON body. Only string-valued `kwargs`
+/// entries are scanned for placeholders; non-string values (numbers,
+/// bools, nested objects/arrays) pass through unchanged.
+fn substitute_template(template: &FunctionSpec, payload: &Value) -> FunctionSpec {
+    let mut spec = template.clone();
+    spec.funcname = substitute_string(&spec.funcname, payload);
+    spec.args = spec.args.iter().map(|a| substitute_string(a, payload)).collect();
+    for value in spec.kwargs.values_mut() {
+        if let Some(s) = value.as_str() {
+            *value = Value::String(substitute_string(s, payload));
+        }
+    }
+    spec
+}
+
+fn substitute_string(input: &str, payload: &Value) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        if let Some(end) = after.find('}') {
+            let field = &after[..end];
+            let value = payload
+                .get(field)
+                .map(|v| v.as_str().map(|s| s.to_owned()).unwrap_or_else(|| v.to_string()))
+                .unwrap_or_default();
+            out.push_str(&value);
+            rest = &after[end + 1..];
+        } else {
+            out.push_str("${");
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_signature_roundtrip() {
+        let key = b"supersecret";
+        let body = b"hello world";
+        let mut mac = HmacSha256::new_from_slice(key).unwrap();
+        mac.update(body);
+        let sig = hex::encode(mac.finalize().into_bytes());
+
+        assert!(verify_signature(key, body, &sig));
+        assert!(verify_signature(key, body, &format!("sha256={sig}")));
+    }
+
+    #[test]
+    fn test_verify_signature_rejects_mismatch() {
+        let key = b"supersecret";
+        assert!(!verify_signature(key, b"hello", "deadbeef"));
+    }
+
+    #[test]
+    fn test_substitute_string() {
+        let payload = serde_json::json!({"branch": "main", "count": 3});
+        assert_eq!(substitute_string("build-${branch}", &payload), "build-main");
+        assert_eq!(substitute_string("${count}", &payload), "3");
+        assert_eq!(substitute_string("no-fields", &payload), "no-fields");
+    }
+
+    #[test]
+    fn test_substitute_template() {
+        let template = FunctionSpec::new("build-${branch}", "cli", "colony");
+        let payload = serde_json::json!({"branch": "main"});
+        let spec = substitute_template(&template, &payload);
+        assert_eq!(spec.funcname, "build-main");
+    }
+
+    #[test]
+    fn test_substitute_template_fills_string_kwargs() {
+        let mut template = FunctionSpec::new("build", "cli", "colony");
+        template.kwargs.insert("ref".to_string(), serde_json::json!("${branch}"));
+        template.kwargs.insert("label".to_string(), serde_json::json!("release-${branch}"));
+        template.kwargs.insert("retries".to_string(), serde_json::json!(3));
+        let payload = serde_json::json!({"branch": "main"});
+
+        let spec = substitute_template(&template, &payload);
+        assert_eq!(spec.kwargs.get("ref"), Some(&serde_json::json!("main")));
+        assert_eq!(spec.kwargs.get("label"), Some(&serde_json::json!("release-main")));
+        // Non-string values pass through untouched.
+        assert_eq!(spec.kwargs.get("retries"), Some(&serde_json::json!(3)));
+    }
+}
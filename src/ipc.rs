@@ -0,0 +1,248 @@
+//! Unix-socket / named-pipe IPC transport, as a lower-latency alternative
+//! to HTTP when the client and colonies server share a host.
+//!
+//! `rpc::send_rpcmsg` normally POSTs to `get_server_url()` over TCP. Once
+//! [`set_ipc_path`] is called, it instead dispatches through here: a single
+//! background task owns the socket (a Unix domain socket on unix, a named
+//! pipe on Windows) for the lifetime of the connection and multiplexes
+//! every in-flight call over it, the same way `pubsub.rs` multiplexes
+//! WebSocket subscriptions over one socket. Outgoing `RPCMsg` JSON is
+//! newline-delimited (the signed/base64-encoded payload itself never
+//! contains a raw newline), and each `RPCReplyMsg` is routed back to its
+//! caller by the `requestid` `rpc::stamp_requestid` adds to the already
+//! signed message — the signing/base64 envelope is unchanged, only the
+//! wire transport differs.
+
+use crate::rpc::RPCError;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+#[cfg(unix)]
+use tokio::net::UnixStream;
+#[cfg(windows)]
+use tokio::net::windows::named_pipe::ClientOptions;
+
+static IPC_PATH: RwLock<Option<String>> = RwLock::new(None);
+static IPC_CONN: Mutex<Option<IpcHandle>> = Mutex::const_new(None);
+
+/// Routes every future call through a Unix domain socket (or, on Windows, a
+/// named pipe) at `path` instead of HTTP. Takes effect on the next
+/// `rpc::send_rpcmsg` call; an in-flight HTTP request, if any, is
+/// unaffected.
+///
+/// # Example
+/// ```
+/// colonyos::set_ipc_path("/var/run/colonies.sock");
+/// ```
+pub fn set_ipc_path(path: &str) {
+    let mut ipc_path = IPC_PATH.write().unwrap();
+    *ipc_path = Some(path.to_owned());
+}
+
+/// Reverts to the default HTTP transport. The next call goes back over
+/// HTTP even if an IPC socket was configured previously.
+pub fn use_http_transport() {
+    let mut ipc_path = IPC_PATH.write().unwrap();
+    *ipc_path = None;
+}
+
+/// Returns the configured IPC socket path, if any, for `rpc::send_rpcmsg`
+/// to dispatch on.
+pub(crate) fn configured_path() -> Option<String> {
+    IPC_PATH.read().unwrap().clone()
+}
+
+struct Shared {
+    pending: Mutex<HashMap<u64, oneshot::Sender<Result<String, RPCError>>>>,
+    next_id: AtomicU64,
+    outgoing: mpsc::UnboundedSender<String>,
+}
+
+#[derive(Clone)]
+struct IpcHandle {
+    shared: Arc<Shared>,
+}
+
+impl IpcHandle {
+    async fn call(&self, msg_json: String) -> Result<String, RPCError> {
+        let id = self.shared.next_id.fetch_add(1, Ordering::Relaxed);
+        let stamped = crate::rpc::stamp_requestid(&msg_json, id);
+        let (tx, rx) = oneshot::channel();
+        self.shared.pending.lock().await.insert(id, tx);
+
+        if self.shared.outgoing.send(stamped).is_err() {
+            self.shared.pending.lock().await.remove(&id);
+            return Err(RPCError::new("IPC connection closed", true));
+        }
+
+        match rx.await {
+            Ok(result) => result,
+            Err(_) => Err(RPCError::new("IPC connection closed", true)),
+        }
+    }
+}
+
+/// Sends an already-composed, JSON-serialized `RPCMsg` over the IPC socket
+/// at `path`, connecting on first use and reconnecting after a prior
+/// connection failure. Mirrors `rpc::send_rpcmsg`'s `Result<String,
+/// RPCError>` contract so `rpc::send_rpcmsg` can dispatch between HTTP and
+/// IPC without its caller knowing which transport is live.
+pub(crate) async fn send_rpcmsg(path: &str, msg: String) -> Result<String, RPCError> {
+    let handle = get_or_connect(path).await?;
+    let result = handle.call(msg).await;
+
+    if let Err(e) = &result {
+        if e.conn_err() {
+            // The background task has already failed every other pending
+            // call and exited; drop the stale handle so the next call
+            // reconnects instead of repeating the same dead call forever.
+            let mut conn = IPC_CONN.lock().await;
+            *conn = None;
+        }
+    }
+
+    result
+}
+
+async fn get_or_connect(path: &str) -> Result<IpcHandle, RPCError> {
+    let mut conn = IPC_CONN.lock().await;
+    if let Some(handle) = conn.as_ref() {
+        return Ok(handle.clone());
+    }
+    let handle = connect(path).await?;
+    *conn = Some(handle.clone());
+    Ok(handle)
+}
+
+#[cfg(unix)]
+async fn connect(path: &str) -> Result<IpcHandle, RPCError> {
+    let stream = UnixStream::connect(path)
+        .await
+        .map_err(|e| RPCError::new(&format!("IPC connect failed: {}", e), true))?;
+    Ok(spawn_connection(stream))
+}
+
+#[cfg(windows)]
+async fn connect(path: &str) -> Result<IpcHandle, RPCError> {
+    let pipe = ClientOptions::new()
+        .open(path)
+        .map_err(|e| RPCError::new(&format!("IPC connect failed: {}", e), true))?;
+    Ok(spawn_connection(pipe))
+}
+
+fn spawn_connection<S>(stream: S) -> IpcHandle
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Send + 'static,
+{
+    let (read_half, mut write_half) = tokio::io::split(stream);
+    let mut reader = BufReader::new(read_half);
+    let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded_channel::<String>();
+
+    let shared = Arc::new(Shared {
+        pending: Mutex::new(HashMap::new()),
+        next_id: AtomicU64::new(1),
+        outgoing: outgoing_tx,
+    });
+    let handle = IpcHandle { shared: shared.clone() };
+
+    tokio::spawn(async move {
+        let mut line = String::new();
+        loop {
+            tokio::select! {
+                outgoing = outgoing_rx.recv() => {
+                    match outgoing {
+                        Some(mut frame) => {
+                            frame.push('\n');
+                            if write_half.write_all(frame.as_bytes()).await.is_err() {
+                                fail_all(&shared).await;
+                                return;
+                            }
+                        }
+                        // Every handle referencing this connection was
+                        // dropped; nothing can call through it anymore.
+                        None => return,
+                    }
+                }
+                result = reader.read_line(&mut line) => {
+                    match result {
+                        Ok(0) => {
+                            // EOF: the server closed the socket.
+                            fail_all(&shared).await;
+                            return;
+                        }
+                        Ok(_) => {
+                            let frame = std::mem::take(&mut line);
+                            route_reply(&shared, frame.trim_end()).await;
+                        }
+                        Err(_) => {
+                            fail_all(&shared).await;
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    handle
+}
+
+async fn route_reply(shared: &Arc<Shared>, text: &str) {
+    if let Ok((requestid, result)) = crate::rpc::decode_rpc_reply(text) {
+        if let Some(tx) = shared.pending.lock().await.remove(&requestid) {
+            let _ = tx.send(result);
+        }
+    }
+    // A frame that doesn't even parse as an `RPCReplyMsg` carries no
+    // `requestid` to route by; drop it rather than guess a recipient.
+}
+
+async fn fail_all(shared: &Arc<Shared>) {
+    let mut pending = shared.pending.lock().await;
+    for (_, tx) in pending.drain() {
+        let _ = tx.send(Err(RPCError::new("IPC connection closed", true)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configured_path_defaults_to_none() {
+        use_http_transport();
+        assert!(configured_path().is_none());
+    }
+
+    #[test]
+    fn test_set_ipc_path_is_visible_via_configured_path() {
+        set_ipc_path("/tmp/colonies-test.sock");
+        assert_eq!(configured_path().as_deref(), Some("/tmp/colonies-test.sock"));
+        use_http_transport();
+        assert!(configured_path().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_fail_all_notifies_every_pending_call() {
+        let (outgoing_tx, _outgoing_rx) = mpsc::unbounded_channel();
+        let shared = Arc::new(Shared {
+            pending: Mutex::new(HashMap::new()),
+            next_id: AtomicU64::new(1),
+            outgoing: outgoing_tx,
+        });
+
+        let (tx1, rx1) = oneshot::channel();
+        let (tx2, rx2) = oneshot::channel();
+        shared.pending.lock().await.insert(1, tx1);
+        shared.pending.lock().await.insert(2, tx2);
+
+        fail_all(&shared).await;
+
+        assert!(rx1.await.unwrap().unwrap_err().conn_err());
+        assert!(rx2.await.unwrap().unwrap_err().conn_err());
+        assert!(shared.pending.lock().await.is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+//! Pluggable signing keys: a common abstraction over secp256k1 (the
+//! original scheme implemented as free functions in [`crate::crypto`]) and
+//! ed25519, so callers that don't need secp256k1's recoverable signatures
+//! can opt into faster ed25519 signing instead. Mirrors the multi-variant
+//! `enum PubKey { P256(..), K256(..) }` design from adenosine's crypto
+//! module.
+//!
+//! `Secp256k1` signatures stay the crate's existing 65-byte `r || s || v`
+//! recoverable format, so verifying one means recovering the signer's ID
+//! and comparing it (see [`crypto::verify`]). `Ed25519` has no recovery
+//! step: it produces a plain 64-byte signature that's verified directly
+//! against the public key, and its ID is the SHA3-256 hash of the raw
+//! verifying key bytes rather than a recovered one.
+
+use crate::crypto::{self, CryptoError};
+use ed25519_dalek::{Signature as Ed25519Signature, SigningKey as Ed25519SigningKey, VerifyingKey as Ed25519VerifyingKey};
+use ed25519_dalek::{Signer as _, Verifier as _};
+use rand::rngs::OsRng;
+use sha3::{Digest, Sha3_256};
+
+/// A keypair capable of signing messages and reporting its own ID.
+pub enum KeyPair {
+    Secp256k1(String),
+    Ed25519(Ed25519SigningKey),
+}
+
+impl KeyPair {
+    /// Generates a new random secp256k1 keypair (existing crate behavior,
+    /// see [`crypto::gen_prvkey`]).
+    pub fn generate_secp256k1() -> KeyPair {
+        KeyPair::Secp256k1(crypto::gen_prvkey())
+    }
+
+    /// Generates a new random ed25519 keypair.
+    pub fn generate_ed25519() -> KeyPair {
+        KeyPair::Ed25519(Ed25519SigningKey::generate(&mut OsRng))
+    }
+
+    /// Signs `message`. Secp256k1 signatures are 65 bytes (`r || s || v`);
+    /// ed25519 signatures are 64 bytes, with no recovery byte.
+    pub fn sign(&self, message: &str) -> Result<Vec<u8>, CryptoError> {
+        match self {
+            KeyPair::Secp256k1(prvkey) => Ok(crypto::try_gen_signature(message, prvkey)?.as_bytes().to_vec()),
+            KeyPair::Ed25519(signing_key) => Ok(signing_key.sign(message.as_bytes()).to_bytes().to_vec()),
+        }
+    }
+
+    /// Derives this keypair's public ID.
+    pub fn id(&self) -> String {
+        self.public_key().id()
+    }
+
+    /// The public half of this keypair, for handing to a verifier that
+    /// shouldn't hold the private key.
+    pub fn public_key(&self) -> PubKey {
+        match self {
+            KeyPair::Secp256k1(prvkey) => PubKey::Secp256k1(crypto::gen_pubkey(prvkey)),
+            KeyPair::Ed25519(signing_key) => PubKey::Ed25519(signing_key.verifying_key()),
+        }
+    }
+}
+
+/// The public half of a [`KeyPair`], used to verify signatures without
+/// holding the private key.
+pub enum PubKey {
+    Secp256k1(String),
+    Ed25519(Ed25519VerifyingKey),
+}
+
+impl PubKey {
+    /// This key's public ID, in the same format [`KeyPair::id`] returns for
+    /// the matching private key.
+    pub fn id(&self) -> String {
+        match self {
+            PubKey::Secp256k1(pubkey_hex) => {
+                let mut hasher = Sha3_256::new();
+                hasher.update(pubkey_hex.as_bytes());
+                hex::encode(hasher.finalize())
+            }
+            PubKey::Ed25519(verifying_key) => {
+                let mut hasher = Sha3_256::new();
+                hasher.update(verifying_key.to_bytes());
+                hex::encode(hasher.finalize())
+            }
+        }
+    }
+
+    /// Verifies that `signature` over `message` was produced by this key's
+    /// matching private key.
+    pub fn verify(&self, message: &str, signature: &[u8]) -> bool {
+        match self {
+            PubKey::Secp256k1(_) => crypto::verify(message, &hex::encode(signature), &self.id()),
+            PubKey::Ed25519(verifying_key) => match Ed25519Signature::try_from(signature) {
+                Ok(sig) => verifying_key.verify(message.as_bytes(), &sig).is_ok(),
+                Err(_) => false,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secp256k1_sign_and_verify_roundtrip() {
+        let keypair = KeyPair::generate_secp256k1();
+        let signature = keypair.sign("hello").unwrap();
+
+        assert!(keypair.public_key().verify("hello", &signature));
+    }
+
+    #[test]
+    fn test_secp256k1_verify_rejects_tampered_message() {
+        let keypair = KeyPair::generate_secp256k1();
+        let signature = keypair.sign("hello").unwrap();
+
+        assert!(!keypair.public_key().verify("goodbye", &signature));
+    }
+
+    #[test]
+    fn test_secp256k1_id_matches_crypto_gen_id() {
+        let keypair = KeyPair::generate_secp256k1();
+        let KeyPair::Secp256k1(prvkey) = &keypair else {
+            unreachable!()
+        };
+        assert_eq!(keypair.id(), crypto::gen_id(prvkey));
+    }
+
+    #[test]
+    fn test_ed25519_sign_and_verify_roundtrip() {
+        let keypair = KeyPair::generate_ed25519();
+        let signature = keypair.sign("hello").unwrap();
+
+        assert_eq!(signature.len(), 64);
+        assert!(keypair.public_key().verify("hello", &signature));
+    }
+
+    #[test]
+    fn test_ed25519_verify_rejects_tampered_message() {
+        let keypair = KeyPair::generate_ed25519();
+        let signature = keypair.sign("hello").unwrap();
+
+        assert!(!keypair.public_key().verify("goodbye", &signature));
+    }
+
+    #[test]
+    fn test_ed25519_verify_rejects_malformed_signature() {
+        let keypair = KeyPair::generate_ed25519();
+        assert!(!keypair.public_key().verify("hello", b"too-short"));
+    }
+
+    #[test]
+    fn test_ed25519_id_is_deterministic() {
+        let keypair = KeyPair::generate_ed25519();
+        assert_eq!(keypair.id(), keypair.public_key().id());
+    }
+
+    #[test]
+    fn test_secp256k1_and_ed25519_keys_produce_different_ids() {
+        let secp = KeyPair::generate_secp256k1();
+        let ed25519 = KeyPair::generate_ed25519();
+        assert_ne!(secp.id(), ed25519.id());
+    }
+}
@@ -16,22 +16,68 @@
 //! }
 //! ```
 
+pub mod artifact;
+pub mod backoff;
+pub mod bench;
+pub mod blueprint;
+pub mod capability;
+pub mod cas;
+pub mod channelpattern;
+pub mod container;
 pub mod core;
+pub mod correlation;
 pub mod crypto;
+pub mod did;
+pub mod disclosure;
+pub mod ecies;
+pub mod exec;
+pub mod executor;
+pub mod fs;
+pub mod gpu;
+pub mod ingress;
+pub mod ipc;
+pub mod keys;
+pub mod logwriter;
+pub mod lua;
+pub mod metrics;
+pub mod mock;
+pub mod pubsub;
+pub mod reconciler;
 pub mod rpc;
+pub mod rt;
+pub mod shell;
+#[cfg(feature = "chrono")]
+pub mod staleness;
+pub mod stream;
+pub mod watch;
 
 // Re-export server configuration functions
-pub use rpc::{set_server_url, get_server_url};
+pub use rpc::{set_server_url, get_server_url, set_root_ca_pem, set_compression_threshold, disable_compression};
+pub use ipc::{set_ipc_path, use_http_transport};
 
 use serde_json::Value;
 use std::collections::HashMap;
 
+/// Issues the protocol-version handshake a client should send on first
+/// contact with a server: every `RPCMsg` already carries `rpc::PROTOCOL_VERSION`,
+/// so a plain round-trip is enough for `send_rpcmsg` to detect and report a
+/// mismatch via `RPCError::version_mismatch`. Returns `Ok(())` when the
+/// versions agree (or the server predates negotiation and reports none).
+pub async fn check_version(prvkey: &str) -> Result<(), rpc::RPCError> {
+    let rpcmsg = rpc::compose_version_rpcmsg(prvkey);
+    rpc::send_rpcmsg(rpcmsg).await?;
+    Ok(())
+}
+
 // ============== Colony Methods ==============
 
 pub async fn add_colony(
     colony: &core::Colony,
     prvkey: &str,
 ) -> Result<core::Colony, rpc::RPCError> {
+    if let Some(mock) = mock::active() {
+        return mock.add_colony(colony);
+    }
     let rpcmsg = rpc::compose_add_colony_rpcmsg(colony, &prvkey.to_owned());
     let reply_json = rpc::send_rpcmsg(rpcmsg).await?;
     let colony: core::Colony = serde_json::from_str(reply_json.as_str()).unwrap();
@@ -72,6 +118,9 @@ pub async fn add_executor(
     executor: &core::Executor,
     prvkey: &str,
 ) -> Result<core::Executor, rpc::RPCError> {
+    if let Some(mock) = mock::active() {
+        return mock.add_executor(executor);
+    }
     let rpcmsg = rpc::compose_add_executor_rpcmsg(executor, &prvkey.to_owned());
     let reply_json = rpc::send_rpcmsg(rpcmsg).await?;
     let executor: core::Executor = serde_json::from_str(reply_json.as_str()).unwrap();
@@ -135,6 +184,9 @@ pub async fn submit(
     spec: &core::FunctionSpec,
     prvkey: &str,
 ) -> Result<core::Process, rpc::RPCError> {
+    if let Some(mock) = mock::active() {
+        return mock.submit(spec);
+    }
     let rpcmsg = rpc::compose_submit_functionspec_rpcmsg(spec, &prvkey.to_owned());
     let reply_json = rpc::send_rpcmsg(rpcmsg).await?;
     let process: core::Process = serde_json::from_str(reply_json.as_str()).unwrap();
@@ -146,6 +198,9 @@ pub async fn assign(
     timeout: i32,
     prvkey: &str,
 ) -> Result<core::Process, rpc::RPCError> {
+    if let Some(mock) = mock::active() {
+        return mock.assign(colonyname);
+    }
     let rpcmsg = rpc::compose_assign_process_rpcmsg(
         &colonyname.to_owned(),
         timeout,
@@ -156,19 +211,79 @@ pub async fn assign(
     Ok(process)
 }
 
+/// Resilient counterpart to [`assign`]: on a connection error, sleeps for
+/// `policy`'s backoff delay (falling back to [`backoff::default_policy`] if
+/// `policy` is `None`) and retries instead of surfacing the error right
+/// away, so a reconciler assigning with a short timeout in a tight loop
+/// backs off gracefully when the server is briefly unreachable instead of
+/// hammering it. Any other error (including a normal assign timeout with
+/// nothing to do) is returned immediately, unretried. Gives up and returns
+/// the last error once the policy's `max_retries` is exhausted.
+pub async fn assign_resilient(
+    colonyname: &str,
+    timeout: i32,
+    prvkey: &str,
+    policy: Option<backoff::BackoffPolicy>,
+) -> Result<core::Process, rpc::RPCError> {
+    let policy = policy.unwrap_or_else(backoff::default_policy);
+    let mut attempt: u32 = 0;
+    loop {
+        match assign(colonyname, timeout, prvkey).await {
+            Ok(process) => return Ok(process),
+            Err(e) if e.conn_err() && policy.should_retry(attempt) => {
+                rt::sleep(policy.delay(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 pub async fn close(processid: &str, prvkey: &str) -> Result<(), rpc::RPCError> {
+    if let Some(mock) = mock::active() {
+        return mock.close(processid);
+    }
     let rpcmsg = rpc::compose_close_process_rpcmsg(&processid.to_owned(), &prvkey.to_owned());
     rpc::send_rpcmsg(rpcmsg).await?;
     Ok(())
 }
 
+/// Closes a process as successful and attaches `output` in the same round
+/// trip, instead of a separate `set_output` call followed by `close`.
+pub async fn close_with_output(
+    processid: &str,
+    output: Vec<String>,
+    prvkey: &str,
+) -> Result<(), rpc::RPCError> {
+    let rpcmsg = rpc::compose_close_process_with_output_rpcmsg(
+        &processid.to_owned(),
+        output,
+        &prvkey.to_owned(),
+    );
+    rpc::send_rpcmsg(rpcmsg).await?;
+    Ok(())
+}
+
 pub async fn fail(processid: &str, prvkey: &str) -> Result<(), rpc::RPCError> {
     let rpcmsg = rpc::compose_fail_process_rpcmsg(&processid.to_owned(), &prvkey.to_owned());
     rpc::send_rpcmsg(rpcmsg).await?;
     Ok(())
 }
 
+/// Closes a process as failed, recording a human-readable `desc` of why it
+/// failed so operators can tell a crashed handler from a rejected function
+/// name without digging through logs.
+pub async fn fail_with(processid: &str, desc: &str, prvkey: &str) -> Result<(), rpc::RPCError> {
+    let rpcmsg =
+        rpc::compose_fail_process_with_desc_rpcmsg(&processid.to_owned(), desc, &prvkey.to_owned());
+    rpc::send_rpcmsg(rpcmsg).await?;
+    Ok(())
+}
+
 pub async fn get_process(processid: &str, prvkey: &str) -> Result<core::Process, rpc::RPCError> {
+    if let Some(mock) = mock::active() {
+        return mock.get_process(processid);
+    }
     let rpcmsg = rpc::compose_get_process_rpcmsg(&processid.to_owned(), &prvkey.to_owned());
     let reply_json = rpc::send_rpcmsg(rpcmsg).await?;
     let process: core::Process = serde_json::from_str(reply_json.as_str()).unwrap();
@@ -219,12 +334,46 @@ pub async fn add_attr(
     attr: &core::Attribute,
     prvkey: &str,
 ) -> Result<core::Attribute, rpc::RPCError> {
+    if let Some(mock) = mock::active() {
+        return mock.add_attr(attr);
+    }
     let rpcmsg = rpc::compose_add_attr_rpcmsg(attr, &prvkey.to_owned());
     let reply_json = rpc::send_rpcmsg(rpcmsg).await?;
     let attr: core::Attribute = serde_json::from_str(reply_json.as_str()).unwrap();
     Ok(attr)
 }
 
+/// Submits a set of selective-disclosure attributes on `processid`: each
+/// `Disclosure`'s digest (not its cleartext key/value) is what gets signed
+/// into the RPC payload, via `rpc::compose_add_sd_attr_rpcmsg`. Callers
+/// hold onto the `Disclosure`s themselves to reveal specific ones later
+/// with `present_attributes`.
+pub async fn add_sd_attributes(
+    processid: &str,
+    colonyname: &str,
+    disclosures: &[disclosure::Disclosure],
+    prvkey: &str,
+) -> Result<(), rpc::RPCError> {
+    let digests: Vec<String> = disclosures.iter().map(disclosure::Disclosure::digest).collect();
+    let rpcmsg = rpc::compose_add_sd_attr_rpcmsg(processid, colonyname, &digests, prvkey);
+    rpc::send_rpcmsg(rpcmsg).await?;
+    Ok(())
+}
+
+/// Reveals `disclosures` (a subset of those submitted via
+/// `add_sd_attributes`) to the server acting as verifier.
+pub async fn present_attributes(
+    processid: &str,
+    colonyname: &str,
+    disclosures: &[disclosure::Disclosure],
+    prvkey: &str,
+) -> Result<(), rpc::RPCError> {
+    let encoded: Vec<String> = disclosures.iter().map(disclosure::Disclosure::encode).collect();
+    let rpcmsg = rpc::compose_present_attrs_rpcmsg(processid, colonyname, &encoded, prvkey);
+    rpc::send_rpcmsg(rpcmsg).await?;
+    Ok(())
+}
+
 // ============== Workflow Methods ==============
 
 pub async fn submit_workflow(
@@ -281,6 +430,9 @@ pub async fn remove_all_processgraphs(
 // ============== Log Methods ==============
 
 pub async fn add_log(log: &core::Log, prvkey: &str) -> Result<(), rpc::RPCError> {
+    if let Some(mock) = mock::active() {
+        return mock.add_log(log);
+    }
     let rpcmsg = rpc::compose_add_log_rpcmsg(log, prvkey);
     rpc::send_rpcmsg(rpcmsg).await?;
     Ok(())
@@ -294,6 +446,9 @@ pub async fn get_logs(
     since: i64,
     prvkey: &str,
 ) -> Result<Vec<core::Log>, rpc::RPCError> {
+    if let Some(mock) = mock::active() {
+        return mock.get_logs(processid);
+    }
     let rpcmsg = rpc::compose_get_logs_rpcmsg(colonyname, processid, executorname, count, since, prvkey);
     let reply_json = rpc::send_rpcmsg(rpcmsg).await?;
     let logs: Result<Vec<core::Log>, _> = serde_json::from_str(reply_json.as_str());
@@ -327,7 +482,7 @@ pub async fn get_logs(
 ///     println!("Process completed!");
 /// }
 /// ```
-#[cfg(not(target_arch = "wasm32"))]
+// Implemented for both native and wasm32 targets (see rpc::send_ws_subscribe_process / send_ws_subscribe_channel).
 pub async fn subscribe_process(
     process: &core::Process,
     state: i32,
@@ -345,6 +500,49 @@ pub async fn subscribe_process(
     rpc::send_ws_subscribe_process(rpcmsg).await
 }
 
+/// Continuously subscribes to `processid`'s state changes over one
+/// WebSocket connection, invoking `callback` for every transition
+/// (`WAITING -> RUNNING -> SUCCESSFUL/FAILED`) instead of returning after
+/// the first one like [`subscribe_process`] does. Keeps streaming until
+/// `callback` returns `false` or the subscription ends.
+pub async fn subscribe_process_stream<F>(
+    processid: &str,
+    executortype: &str,
+    state: i32,
+    timeout: i32,
+    colonyname: &str,
+    prvkey: &str,
+    callback: F,
+) -> Result<(), rpc::RPCError>
+where
+    F: FnMut(core::Process) -> bool,
+{
+    let rpcmsg = rpc::compose_subscribe_process_rpcmsg(processid, executortype, state, timeout, colonyname, prvkey);
+    rpc::send_ws_subscribe_process_stream(rpcmsg, timeout, callback).await
+}
+
+/// Continuously subscribes to blueprint lifecycle/convergence notifications
+/// (`Added`/`StatusUpdated`/`Reconciled`/`Removed`) for `colonyname` over one
+/// WebSocket connection, invoking `callback` for every event. `kind` and
+/// `name_filter` narrow the subscription server-side (empty matches any);
+/// keeps streaming until `callback` returns `false` or the subscription
+/// ends. See [`crate::stream::subscribe_blueprint_events`] for a
+/// `Stream`-returning wrapper around this.
+pub async fn subscribe_blueprint_events_stream<F>(
+    colonyname: &str,
+    kind: &str,
+    name_filter: &str,
+    timeout: i32,
+    prvkey: &str,
+    callback: F,
+) -> Result<(), rpc::RPCError>
+where
+    F: FnMut(core::BlueprintEvent) -> bool,
+{
+    let rpcmsg = rpc::compose_subscribe_blueprint_events_rpcmsg(colonyname, kind, name_filter, timeout, prvkey);
+    rpc::send_ws_subscribe_blueprint_events(rpcmsg, timeout, callback).await
+}
+
 /// Subscribe to channel messages via WebSocket.
 ///
 /// This function opens a WebSocket connection to receive real-time channel messages.
@@ -381,7 +579,7 @@ pub async fn subscribe_process(
 ///     ).await.unwrap();
 /// }
 /// ```
-#[cfg(not(target_arch = "wasm32"))]
+// Implemented for both native and wasm32 targets (see rpc::send_ws_subscribe_process / send_ws_subscribe_channel).
 pub async fn subscribe_channel<F>(
     processid: &str,
     channelname: &str,
@@ -393,6 +591,9 @@ pub async fn subscribe_channel<F>(
 where
     F: FnMut(Vec<core::ChannelEntry>) -> bool,
 {
+    if let Some(mock) = mock::active() {
+        return mock.subscribe_channel(processid, channelname, afterseq, timeout, callback).await;
+    }
     let rpcmsg = rpc::compose_subscribe_channel_rpcmsg(
         processid,
         channelname,
@@ -409,30 +610,197 @@ pub async fn channel_append(
     processid: &str,
     channelname: &str,
     sequence: i64,
-    data: &str,
-    data_type: &str,
+    data: &[u8],
+    content_type: &str,
     inreplyto: i64,
     prvkey: &str,
 ) -> Result<core::ChannelEntry, rpc::RPCError> {
-    let rpcmsg = rpc::compose_channel_append_rpcmsg(processid, channelname, sequence, data, data_type, inreplyto, prvkey);
+    if let Some(mock) = mock::active() {
+        return mock.channel_append(processid, channelname, data, content_type, inreplyto);
+    }
+    let rpcmsg =
+        rpc::compose_channel_append_rpcmsg(processid, channelname, sequence, data, content_type, inreplyto, prvkey);
     let reply_json = rpc::send_rpcmsg(rpcmsg).await?;
     let entry: core::ChannelEntry = serde_json::from_str(reply_json.as_str()).unwrap_or_default();
     Ok(entry)
 }
 
+/// Convenience wrapper for appending a UTF-8 string with
+/// `content_type = "text/plain"`.
+pub async fn channel_append_text(
+    processid: &str,
+    channelname: &str,
+    sequence: i64,
+    text: &str,
+    inreplyto: i64,
+    prvkey: &str,
+) -> Result<core::ChannelEntry, rpc::RPCError> {
+    channel_append(
+        processid,
+        channelname,
+        sequence,
+        text.as_bytes(),
+        core::CONTENT_TYPE_TEXT,
+        inreplyto,
+        prvkey,
+    )
+    .await
+}
+
+/// Convenience wrapper for appending a pre-serialized JSON payload with
+/// `content_type = "application/json"`.
+pub async fn channel_append_json(
+    processid: &str,
+    channelname: &str,
+    sequence: i64,
+    json: &[u8],
+    inreplyto: i64,
+    prvkey: &str,
+) -> Result<core::ChannelEntry, rpc::RPCError> {
+    channel_append(
+        processid,
+        channelname,
+        sequence,
+        json,
+        core::CONTENT_TYPE_JSON,
+        inreplyto,
+        prvkey,
+    )
+    .await
+}
+
+/// Convenience wrapper for appending an untyped binary payload (e.g.
+/// protobuf/CBOR) with `content_type = "application/octet-stream"`.
+pub async fn channel_append_bytes(
+    processid: &str,
+    channelname: &str,
+    sequence: i64,
+    data: &[u8],
+    inreplyto: i64,
+    prvkey: &str,
+) -> Result<core::ChannelEntry, rpc::RPCError> {
+    channel_append(
+        processid,
+        channelname,
+        sequence,
+        data,
+        core::CONTENT_TYPE_OCTET_STREAM,
+        inreplyto,
+        prvkey,
+    )
+    .await
+}
+
+/// Resilient counterpart to [`channel_append`]; see [`assign_resilient`]
+/// for the retry contract.
+pub async fn channel_append_resilient(
+    processid: &str,
+    channelname: &str,
+    sequence: i64,
+    data: &[u8],
+    content_type: &str,
+    inreplyto: i64,
+    prvkey: &str,
+    policy: Option<backoff::BackoffPolicy>,
+) -> Result<core::ChannelEntry, rpc::RPCError> {
+    let policy = policy.unwrap_or_else(backoff::default_policy);
+    let mut attempt: u32 = 0;
+    loop {
+        match channel_append(processid, channelname, sequence, data, content_type, inreplyto, prvkey).await {
+            Ok(entry) => return Ok(entry),
+            Err(e) if e.conn_err() && policy.should_retry(attempt) => {
+                rt::sleep(policy.delay(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 pub async fn channel_read(
     processid: &str,
     channelname: &str,
     start: i64,
     count: i32,
+    content_type: &str,
     prvkey: &str,
 ) -> Result<Vec<core::ChannelEntry>, rpc::RPCError> {
-    let rpcmsg = rpc::compose_channel_read_rpcmsg(processid, channelname, start, count, prvkey);
+    if let Some(mock) = mock::active() {
+        return mock.channel_read(processid, channelname, start, count, content_type);
+    }
+    let rpcmsg = rpc::compose_channel_read_rpcmsg(processid, channelname, start, count, content_type, prvkey);
     let reply_json = rpc::send_rpcmsg(rpcmsg).await?;
     let entries: Result<Vec<core::ChannelEntry>, _> = serde_json::from_str(reply_json.as_str());
     Ok(entries.unwrap_or_default())
 }
 
+/// Resilient counterpart to [`channel_read`]; see [`assign_resilient`] for
+/// the retry contract.
+pub async fn channel_read_resilient(
+    processid: &str,
+    channelname: &str,
+    start: i64,
+    count: i32,
+    content_type: &str,
+    prvkey: &str,
+    policy: Option<backoff::BackoffPolicy>,
+) -> Result<Vec<core::ChannelEntry>, rpc::RPCError> {
+    let policy = policy.unwrap_or_else(backoff::default_policy);
+    let mut attempt: u32 = 0;
+    loop {
+        match channel_read(processid, channelname, start, count, content_type, prvkey).await {
+            Ok(entries) => return Ok(entries),
+            Err(e) if e.conn_err() && policy.should_retry(attempt) => {
+                rt::sleep(policy.delay(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Long-polls `channelname` for entries in `(cursor.seq, end_seq]`, closing
+/// the gap a plain `channel_read` poll loop can leave between two reads:
+/// the server blocks until a new entry lands in the window or `timeout`
+/// elapses. Returns the new entries alongside `cursor` advanced past the
+/// highest sequence delivered, so persisting the returned cursor and
+/// passing it back into the next call resumes exactly where this one left
+/// off, even across a reconnect.
+pub async fn channel_poll_range(
+    processid: &str,
+    channelname: &str,
+    cursor: core::ChannelCursor,
+    end_seq: i64,
+    timeout: i32,
+    prvkey: &str,
+) -> Result<(Vec<core::ChannelEntry>, core::ChannelCursor), rpc::RPCError> {
+    let rpcmsg =
+        rpc::compose_channel_poll_range_rpcmsg(processid, channelname, cursor.seq + 1, end_seq, timeout, prvkey);
+    let reply_json = rpc::send_rpcmsg(rpcmsg).await?;
+    let entries: Vec<core::ChannelEntry> = serde_json::from_str(reply_json.as_str()).unwrap_or_default();
+    let mut cursor = cursor;
+    cursor.advance(&entries);
+    Ok((entries, cursor))
+}
+
+/// Appends several `(data, content_type)` payloads to `channelname`
+/// atomically in one RPC frame, sequenced starting at `start_sequence`,
+/// instead of one `channel_append` round trip per payload.
+pub async fn channel_append_batch(
+    processid: &str,
+    channelname: &str,
+    start_sequence: i64,
+    items: &[(&[u8], &str)],
+    inreplyto: i64,
+    prvkey: &str,
+) -> Result<Vec<core::ChannelEntry>, rpc::RPCError> {
+    let rpcmsg =
+        rpc::compose_channel_append_batch_rpcmsg(processid, channelname, start_sequence, items, inreplyto, prvkey);
+    let reply_json = rpc::send_rpcmsg(rpcmsg).await?;
+    let entries: Vec<core::ChannelEntry> = serde_json::from_str(reply_json.as_str()).unwrap_or_default();
+    Ok(entries)
+}
+
 // ============== Statistics Methods ==============
 
 pub async fn get_statistics(
@@ -445,6 +813,29 @@ pub async fn get_statistics(
     Ok(stats)
 }
 
+/// Reports an executor's live resource availability, so the colony
+/// scheduler can avoid assigning processes to executors that lack the
+/// requested CPU/memory/disk/GPU.
+pub async fn report_capacity(
+    capacity: &core::ExecutorCapacity,
+    prvkey: &str,
+) -> Result<(), rpc::RPCError> {
+    let rpcmsg = rpc::compose_report_capacity_rpcmsg(capacity, prvkey);
+    rpc::send_rpcmsg(rpcmsg).await?;
+    Ok(())
+}
+
+/// Fetches the last-reported capacity for every executor in `colonyname`.
+pub async fn get_capacities(
+    colonyname: &str,
+    prvkey: &str,
+) -> Result<Vec<core::ExecutorCapacity>, rpc::RPCError> {
+    let rpcmsg = rpc::compose_get_capacities_rpcmsg(colonyname, prvkey);
+    let reply_json = rpc::send_rpcmsg(rpcmsg).await?;
+    let capacities: Vec<core::ExecutorCapacity> = serde_json::from_str(reply_json.as_str()).unwrap_or_default();
+    Ok(capacities)
+}
+
 // ============== Function Registry Methods ==============
 
 pub async fn add_function(
@@ -520,16 +911,74 @@ pub async fn get_blueprint_definitions(
     Ok(definitions.unwrap_or_default())
 }
 
+/// Removes a blueprint definition. Tolerates `name` not being registered:
+/// unregistering something that's already gone is the outcome the caller
+/// wanted, not a failure, so cleanup code doesn't need to guard every call
+/// with a prior existence check. A connection error on the existence check
+/// is propagated rather than swallowed, since it doesn't tell us whether
+/// `name` exists or not.
 pub async fn remove_blueprint_definition(
     colonyname: &str,
     name: &str,
     prvkey: &str,
 ) -> Result<(), rpc::RPCError> {
+    if let Err(e) = get_blueprint_definition(colonyname, name, prvkey).await {
+        if e.conn_err() {
+            return Err(e);
+        }
+        return Ok(());
+    }
     let rpcmsg = rpc::compose_remove_blueprint_definition_rpcmsg(colonyname, name, prvkey);
     rpc::send_rpcmsg(rpcmsg).await?;
     Ok(())
 }
 
+/// Returned by [`add_blueprint_definition_guarded`] and
+/// [`add_blueprint_guarded`] instead of silently overwriting a conflicting
+/// registration.
+#[derive(Debug, Clone)]
+pub enum RegisterError {
+    /// The RPC call itself failed.
+    Rpc(rpc::RPCError),
+    /// `name` is already registered with different content; see
+    /// [`blueprint::Conflict`] for which fields differ.
+    Conflict(blueprint::Conflict),
+}
+
+impl std::fmt::Display for RegisterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RegisterError::Rpc(e) => write!(f, "{e}"),
+            RegisterError::Conflict(c) => write!(f, "{c}"),
+        }
+    }
+}
+
+/// Like [`add_blueprint_definition`], but checks for a conflicting
+/// registration under the same name first: an identical definition is a
+/// no-op that returns the one already on record, a differing one is
+/// rejected with [`RegisterError::Conflict`] instead of silently
+/// overwriting it. Mirrors slobrok's register semantics: register is a
+/// no-op-or-error when it would conflict. A connection error on the
+/// existence check is propagated rather than treated as "not registered",
+/// since otherwise a transient failure would skip the conflict check
+/// entirely and add unconditionally.
+pub async fn add_blueprint_definition_guarded(
+    definition: &core::BlueprintDefinition,
+    prvkey: &str,
+) -> Result<core::BlueprintDefinition, RegisterError> {
+    match get_blueprint_definition(&definition.metadata.colonyname, &definition.metadata.name, prvkey).await {
+        Ok(existing) => match blueprint::blueprint_definition_conflict(&existing, definition) {
+            Some(conflict) => Err(RegisterError::Conflict(conflict)),
+            None => Ok(existing),
+        },
+        Err(e) if e.conn_err() => Err(RegisterError::Rpc(e)),
+        Err(_) => add_blueprint_definition(definition, prvkey)
+            .await
+            .map_err(RegisterError::Rpc),
+    }
+}
+
 // ============== Blueprint Methods ==============
 
 pub async fn add_blueprint(
@@ -576,16 +1025,43 @@ pub async fn update_blueprint(
     Ok(blueprint)
 }
 
+/// Removes a blueprint. Tolerates `name` not existing, for the same reason
+/// [`remove_blueprint_definition`] does: cleanup code shouldn't have to
+/// guard every call with a prior existence check. A connection error on the
+/// existence check is propagated rather than swallowed, since it doesn't
+/// tell us whether `name` exists or not.
 pub async fn remove_blueprint(
     colonyname: &str,
     name: &str,
     prvkey: &str,
 ) -> Result<(), rpc::RPCError> {
+    if let Err(e) = get_blueprint(colonyname, name, prvkey).await {
+        if e.conn_err() {
+            return Err(e);
+        }
+        return Ok(());
+    }
     let rpcmsg = rpc::compose_remove_blueprint_rpcmsg(colonyname, name, prvkey);
     rpc::send_rpcmsg(rpcmsg).await?;
     Ok(())
 }
 
+/// Like [`add_blueprint`], but checks for a conflicting registration under
+/// the same name first; see [`add_blueprint_definition_guarded`].
+pub async fn add_blueprint_guarded(
+    blueprint: &core::Blueprint,
+    prvkey: &str,
+) -> Result<core::Blueprint, RegisterError> {
+    match get_blueprint(&blueprint.metadata.colonyname, &blueprint.metadata.name, prvkey).await {
+        Ok(existing) => match blueprint::blueprint_conflict(&existing, blueprint) {
+            Some(conflict) => Err(RegisterError::Conflict(conflict)),
+            None => Ok(existing),
+        },
+        Err(e) if e.conn_err() => Err(RegisterError::Rpc(e)),
+        Err(_) => add_blueprint(blueprint, prvkey).await.map_err(RegisterError::Rpc),
+    }
+}
+
 pub async fn update_blueprint_status(
     colonyname: &str,
     name: &str,
@@ -597,6 +1073,65 @@ pub async fn update_blueprint_status(
     Ok(())
 }
 
+/// Returned by [`update_blueprint_status_guarded`] when a write loses a
+/// race against a newer observation.
+#[derive(Debug, Clone)]
+pub enum StatusUpdateError {
+    /// The RPC call itself failed.
+    Rpc(rpc::RPCError),
+    /// `generation` is older than the `observedGeneration` already stamped
+    /// into the blueprint's status; the caller must re-read the blueprint
+    /// (its spec may have moved on again) before retrying, rather than
+    /// blindly resubmitting the same write.
+    Superseded { attempted: i64, observed: i64 },
+}
+
+impl std::fmt::Display for StatusUpdateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            StatusUpdateError::Rpc(e) => write!(f, "{e}"),
+            StatusUpdateError::Superseded { attempted, observed } => write!(
+                f,
+                "update_blueprint_status_guarded: generation {attempted} is stale, status already observed generation {observed}"
+            ),
+        }
+    }
+}
+
+/// Like [`update_blueprint_status`], but guards against a slow reconciler
+/// clobbering a newer observed state: reads the blueprint's current
+/// `observedGeneration` (stamped into `status` by a prior guarded write, or
+/// `0` if there isn't one yet) and rejects the write with
+/// [`StatusUpdateError::Superseded`] instead of sending it when `generation`
+/// is older. Mirrors the fix for out-of-order terminal status updates in
+/// Mesos agents: keep the highest-seen version, drop anything stale, and
+/// surface the rejection so the caller knows to re-read before retrying.
+pub async fn update_blueprint_status_guarded(
+    colonyname: &str,
+    name: &str,
+    mut status: HashMap<String, Value>,
+    generation: i64,
+    prvkey: &str,
+) -> Result<(), StatusUpdateError> {
+    let current = get_blueprint(colonyname, name, prvkey).await.map_err(StatusUpdateError::Rpc)?;
+    let observed = current
+        .status
+        .get("observedGeneration")
+        .and_then(|v| v.as_i64())
+        .unwrap_or(0);
+    if generation < observed {
+        return Err(StatusUpdateError::Superseded {
+            attempted: generation,
+            observed,
+        });
+    }
+
+    status.insert("observedGeneration".to_owned(), Value::from(generation));
+    update_blueprint_status(colonyname, name, status, prvkey)
+        .await
+        .map_err(StatusUpdateError::Rpc)
+}
+
 pub async fn reconcile_blueprint(
     colonyname: &str,
     name: &str,
@@ -693,7 +1228,7 @@ mod tests {
         assert_eq!(executor.executorid, "exec-id");
         assert_eq!(executor.executortype, "cli");
         assert_eq!(executor.colonyname, "test-colony");
-        assert_eq!(executor.state, 0);
+        assert_eq!(executor.state, ExecutorState::Pending);
     }
 
     #[test]
@@ -722,7 +1257,7 @@ mod tests {
         assert_eq!(attr.targetid, "process-123");
         assert_eq!(attr.key, "result");
         assert_eq!(attr.value, "success");
-        assert_eq!(attr.attributetype, OUT);
+        assert_eq!(attr.attributetype, AttributeType::Out);
     }
 
     #[test]
@@ -748,6 +1283,39 @@ mod tests {
         assert_eq!(ENV, 4);
     }
 
+    #[test]
+    fn test_process_state_serializes_as_bare_integer() {
+        assert_eq!(serde_json::to_string(&ProcessState::Running).unwrap(), "1");
+        assert_eq!(serde_json::to_string(&ProcessState::Unknown(42)).unwrap(), "42");
+    }
+
+    #[test]
+    fn test_process_state_deserializes_unrecognized_value_as_unknown() {
+        let state: ProcessState = serde_json::from_str("99").unwrap();
+        assert_eq!(state, ProcessState::Unknown(99));
+    }
+
+    #[test]
+    fn test_executor_state_default_matches_pending() {
+        assert_eq!(ExecutorState::default(), ExecutorState::Pending);
+        assert_eq!(serde_json::to_string(&ExecutorState::default()).unwrap(), "0");
+    }
+
+    #[test]
+    fn test_attribute_type_round_trips_through_json() {
+        for (variant, wire) in [
+            (AttributeType::In, 0),
+            (AttributeType::Out, 1),
+            (AttributeType::Err, 2),
+            (AttributeType::Env, 4),
+        ] {
+            let json = serde_json::to_string(&variant).unwrap();
+            assert_eq!(json, wire.to_string());
+            let round_tripped: AttributeType = serde_json::from_str(&json).unwrap();
+            assert_eq!(round_tripped, variant);
+        }
+    }
+
     #[test]
     fn test_functionspec_serialization() {
         let mut spec = FunctionSpec::new("test-func", "cli", "test-colony");
@@ -784,7 +1352,7 @@ mod tests {
 
         let process: Process = serde_json::from_str(json).unwrap();
         assert_eq!(process.processid, "proc-123");
-        assert_eq!(process.state, RUNNING);
+        assert_eq!(process.state, ProcessState::Running);
         assert!(process.isassigned);
     }
 
@@ -812,7 +1380,7 @@ mod tests {
             colonyname: "test-colony".to_string(),
             executorname: "executor-1".to_string(),
             message: "Test message".to_string(),
-            timestamp: 1234567890,
+            timestamp: "1234567890".to_string(),
         };
 
         let json = serde_json::to_string(&log).unwrap();
@@ -820,7 +1388,7 @@ mod tests {
 
         assert_eq!(parsed.processid, "proc-123");
         assert_eq!(parsed.message, "Test message");
-        assert_eq!(parsed.timestamp, 1234567890);
+        assert_eq!(parsed.timestamp, "1234567890");
     }
 
     #[test]
@@ -843,6 +1411,70 @@ mod tests {
         assert_eq!(entry.senderid, "abc123");
     }
 
+    #[test]
+    fn test_channel_entry_payload_typed_json() {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+        let entry = ChannelEntry {
+            payload: STANDARD.encode(r#"{"a":1}"#),
+            contenttype: core::CONTENT_TYPE_JSON.to_string(),
+            ..ChannelEntry::default()
+        };
+        assert_eq!(entry.payload_typed().unwrap(), core::ChannelPayload::Json(serde_json::json!({"a": 1})));
+    }
+
+    #[test]
+    fn test_channel_entry_payload_typed_text() {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+        let entry = ChannelEntry {
+            payload: STANDARD.encode("hello"),
+            contenttype: core::CONTENT_TYPE_TEXT.to_string(),
+            ..ChannelEntry::default()
+        };
+        assert_eq!(entry.payload_typed().unwrap(), core::ChannelPayload::Text("hello".to_string()));
+    }
+
+    #[test]
+    fn test_channel_entry_payload_typed_unknown_falls_back_to_other() {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+        let entry = ChannelEntry {
+            payload: STANDARD.encode("custom"),
+            contenttype: "application/vnd.custom".to_string(),
+            ..ChannelEntry::default()
+        };
+        match entry.payload_typed().unwrap() {
+            core::ChannelPayload::Other(tag, bytes) => {
+                assert_eq!(tag, "application/vnd.custom");
+                assert_eq!(bytes, b"custom");
+            }
+            other => panic!("expected Other, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_channel_entry_decode_rejects_non_json_contenttype() {
+        let entry = ChannelEntry {
+            contenttype: core::CONTENT_TYPE_TEXT.to_string(),
+            ..ChannelEntry::default()
+        };
+        assert!(entry.decode::<serde_json::Value>().is_err());
+    }
+
+    #[test]
+    fn test_channel_entry_decode_json_into_typed_struct() {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+        #[derive(serde::Deserialize, PartialEq, Debug)]
+        struct Point {
+            x: i32,
+            y: i32,
+        }
+        let entry = ChannelEntry {
+            payload: STANDARD.encode(r#"{"x":1,"y":2}"#),
+            contenttype: core::CONTENT_TYPE_JSON.to_string(),
+            ..ChannelEntry::default()
+        };
+        assert_eq!(entry.decode::<Point>().unwrap(), Point { x: 1, y: 2 });
+    }
+
     #[test]
     fn test_statistics_default() {
         let stats = Statistics::default();
@@ -1260,6 +1892,7 @@ mod tests {
             inreplyto: 0,
             timestamp: "".to_string(),
             senderid: "".to_string(),
+            contenttype: "".to_string(),
         };
         let bytes = entry.payload_bytes();
         assert_eq!(bytes, b"Hello World");
@@ -1281,6 +1914,7 @@ mod tests {
             inreplyto: 0,
             timestamp: "".to_string(),
             senderid: "".to_string(),
+            contenttype: "".to_string(),
         };
         // Should return empty vec on invalid base64
         let bytes = entry.payload_bytes();
@@ -1298,53 +1932,110 @@ mod tests {
         assert_eq!(entry.senderid, "");
     }
 
+    #[test]
+    fn test_channel_cursor_default_starts_before_seq_zero() {
+        let cursor = ChannelCursor::default();
+        assert_eq!(cursor.seq, -1);
+    }
+
+    #[test]
+    fn test_channel_cursor_after() {
+        let cursor = ChannelCursor::after(7);
+        assert_eq!(cursor.seq, 7);
+    }
+
+    #[test]
+    fn test_channel_cursor_advance_is_monotonic() {
+        let mut cursor = ChannelCursor::new();
+        cursor.advance(&[
+            ChannelEntry { sequence: 3, ..ChannelEntry::default() },
+            ChannelEntry { sequence: 1, ..ChannelEntry::default() },
+        ]);
+        assert_eq!(cursor.seq, 3);
+
+        // A later batch with only lower sequences never moves it backwards.
+        cursor.advance(&[ChannelEntry { sequence: 0, ..ChannelEntry::default() }]);
+        assert_eq!(cursor.seq, 3);
+    }
+
+    #[test]
+    fn test_executor_capacity_can_fit() {
+        let mut capacity = ExecutorCapacity::new("executor-1", "test-colony");
+        capacity.freecpucores = 4;
+        capacity.freememorybytes = 2 * 1024 * 1024 * 1024;
+        capacity.freediskbytes = 50 * 1024 * 1024 * 1024;
+        capacity.freegpucount = 1;
+
+        assert!(capacity.can_fit(2, 1024 * 1024 * 1024, 1024 * 1024 * 1024, 1));
+        assert!(!capacity.can_fit(8, 0, 0, 0));
+        assert!(!capacity.can_fit(0, 0, 0, 2));
+    }
+
+    #[test]
+    fn test_executor_capacity_display_shows_human_readable_sizes() {
+        let mut capacity = ExecutorCapacity::new("executor-1", "test-colony");
+        capacity.freediskbytes = 10 * 1024 * 1024 * 1024;
+        capacity.freememorybytes = 512 * 1024 * 1024;
+        capacity.freecpucores = 4;
+        capacity.freegpucount = 1;
+
+        let rendered = format!("{capacity}");
+        assert!(rendered.contains("executor-1"));
+        assert!(rendered.contains("GiB"));
+        assert!(rendered.contains("MiB"));
+    }
+
     #[test]
     fn test_attribute_types() {
         let attr_in = Attribute {
-            attributetype: IN,
+            attributetype: AttributeType::In,
             ..Attribute::new("colony", "process", "key", "value")
         };
-        assert_eq!(attr_in.attributetype, 0);
+        assert_eq!(attr_in.attributetype, AttributeType::In);
 
         let attr_err = Attribute {
-            attributetype: ERR,
+            attributetype: AttributeType::Err,
             ..Attribute::new("colony", "process", "key", "error")
         };
-        assert_eq!(attr_err.attributetype, 2);
+        assert_eq!(attr_err.attributetype, AttributeType::Err);
 
         let attr_env = Attribute {
-            attributetype: ENV,
+            attributetype: AttributeType::Env,
             ..Attribute::new("colony", "process", "key", "env_val")
         };
-        assert_eq!(attr_env.attributetype, 4);
+        assert_eq!(attr_env.attributetype, AttributeType::Env);
     }
 
     #[test]
     fn test_executor_states() {
         let mut executor = Executor::new("name", "id", "cli", "colony");
-        executor.state = PENDING;
-        assert_eq!(executor.state, 0);
+        executor.state = ExecutorState::Pending;
+        assert_eq!(executor.state, ExecutorState::Pending);
 
-        executor.state = APPROVED;
-        assert_eq!(executor.state, 1);
+        executor.state = ExecutorState::Approved;
+        assert_eq!(executor.state, ExecutorState::Approved);
 
-        executor.state = REJECTED;
-        assert_eq!(executor.state, 2);
+        executor.state = ExecutorState::Rejected;
+        assert_eq!(executor.state, ExecutorState::Rejected);
     }
 
     #[test]
     fn test_process_states() {
         let process_json_waiting = r#"{"processid": "p1", "state": 0, "spec": {"funcname": "test", "conditions": {}}}"#;
         let p: Process = serde_json::from_str(process_json_waiting).unwrap();
-        assert_eq!(p.state, WAITING);
+        assert_eq!(p.state, ProcessState::Waiting);
 
         let process_json_success = r#"{"processid": "p2", "state": 2, "spec": {"funcname": "test", "conditions": {}}}"#;
         let p: Process = serde_json::from_str(process_json_success).unwrap();
-        assert_eq!(p.state, SUCCESS);
+        assert_eq!(p.state, ProcessState::Success);
 
         let process_json_failed = r#"{"processid": "p3", "state": 3, "spec": {"funcname": "test", "conditions": {}}}"#;
         let p: Process = serde_json::from_str(process_json_failed).unwrap();
-        assert_eq!(p.state, FAILED);
+        assert_eq!(p.state, ProcessState::Failed);
+
+        let process_json_unknown = r#"{"processid": "p4", "state": 99, "spec": {"funcname": "test", "conditions": {}}}"#;
+        let p: Process = serde_json::from_str(process_json_unknown).unwrap();
+        assert_eq!(p.state, ProcessState::Unknown(99));
     }
 
     #[test]
@@ -1452,7 +2143,7 @@ mod tests {
         let process: Process = serde_json::from_str(json).unwrap();
         assert_eq!(process.attributes.len(), 2);
         assert_eq!(process.attributes[0].key, "key1");
-        assert_eq!(process.attributes[1].attributetype, OUT);
+        assert_eq!(process.attributes[1].attributetype, AttributeType::Out);
     }
 
     #[test]
@@ -1616,6 +2307,85 @@ mod tests {
         assert_eq!(allocs.projects.get("project1").unwrap().allocatedcpu, 100);
     }
 
+    #[test]
+    fn test_try_reserve_bumps_used_counters_within_headroom() {
+        let mut allocs = Allocations::default();
+        allocs.projects.insert(
+            "project1".to_string(),
+            Project {
+                allocatedcpu: 100,
+                usedcpu: 50,
+                allocatedgpu: 10,
+                usedgpu: 5,
+                allocatedstorage: 1000,
+                usedstorage: 500,
+            },
+        );
+
+        assert!(allocs.try_reserve("project1", 20, 2, 200).is_ok());
+
+        let project = allocs.projects.get("project1").unwrap();
+        assert_eq!(project.usedcpu, 70);
+        assert_eq!(project.usedgpu, 7);
+        assert_eq!(project.usedstorage, 700);
+    }
+
+    #[test]
+    fn test_try_reserve_rejects_overcommit_without_mutating() {
+        let mut allocs = Allocations::default();
+        allocs.projects.insert(
+            "project1".to_string(),
+            Project {
+                allocatedcpu: 100,
+                usedcpu: 90,
+                allocatedgpu: 10,
+                usedgpu: 5,
+                allocatedstorage: 1000,
+                usedstorage: 500,
+            },
+        );
+
+        let err = allocs.try_reserve("project1", 20, 1, 10).unwrap_err();
+        assert_eq!(err.dimension, QuotaDimension::Cpu);
+        assert_eq!(err.remaining, 10);
+
+        let project = allocs.projects.get("project1").unwrap();
+        assert_eq!(project.usedcpu, 90);
+        assert_eq!(project.usedgpu, 5);
+    }
+
+    #[test]
+    fn test_try_reserve_against_unknown_project_has_zero_headroom() {
+        let mut allocs = Allocations::default();
+        let err = allocs.try_reserve("missing", 1, 0, 0).unwrap_err();
+        assert_eq!(err.dimension, QuotaDimension::Cpu);
+        assert_eq!(err.remaining, 0);
+        assert!(allocs.projects.is_empty());
+    }
+
+    #[test]
+    fn test_release_decrements_used_counters_and_floors_at_zero() {
+        let mut allocs = Allocations::default();
+        allocs.projects.insert(
+            "project1".to_string(),
+            Project {
+                allocatedcpu: 100,
+                usedcpu: 10,
+                allocatedgpu: 10,
+                usedgpu: 1,
+                allocatedstorage: 1000,
+                usedstorage: 100,
+            },
+        );
+
+        allocs.release("project1", 50, 5, 50);
+
+        let project = allocs.projects.get("project1").unwrap();
+        assert_eq!(project.usedcpu, 0);
+        assert_eq!(project.usedgpu, 0);
+        assert_eq!(project.usedstorage, 50);
+    }
+
     #[test]
     fn test_executor_with_full_details() {
         let json = r#"{
@@ -1631,7 +2401,7 @@ mod tests {
         }"#;
 
         let executor: Executor = serde_json::from_str(json).unwrap();
-        assert_eq!(executor.state, APPROVED);
+        assert_eq!(executor.state, ExecutorState::Approved);
         assert_eq!(executor.locationname, "us-west-2");
         assert_eq!(executor.blueprintid, "bp-456");
         assert_eq!(executor.commissiontime, "2025-01-01T00:00:00Z");
@@ -1676,8 +2446,44 @@ mod tests {
         }"#;
 
         let pg: ProcessGraph = serde_json::from_str(json).unwrap();
-        assert_eq!(pg.state, RUNNING);
+        assert_eq!(pg.state, ProcessState::Running);
         assert_eq!(pg.rootprocessids.len(), 1);
         assert_eq!(pg.processids.len(), 3);
     }
+
+    #[tokio::test]
+    async fn test_assign_resilient_does_not_retry_non_connection_errors() {
+        // "no waiting process available" isn't a connection error, so
+        // `assign_resilient` should surface it immediately rather than
+        // sleeping through retries that will never succeed.
+        mock::install(mock::MockServer::new());
+        let policy = backoff::BackoffPolicy::new().max_retries(5);
+        let result = assign_resilient("test-colony", 1, "prvkey", Some(policy)).await;
+        mock::uninstall();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_status_update_error_display() {
+        let err = StatusUpdateError::Superseded {
+            attempted: 3,
+            observed: 5,
+        };
+        assert_eq!(
+            format!("{err}"),
+            "update_blueprint_status_guarded: generation 3 is stale, status already observed generation 5"
+        );
+    }
+
+    #[test]
+    fn test_register_error_conflict_display() {
+        let err = RegisterError::Conflict(blueprint::Conflict {
+            name: "thermostat-def".to_owned(),
+            differing_fields: vec!["spec.schema".to_owned()],
+        });
+        assert_eq!(
+            format!("{err}"),
+            "conflicting registration for 'thermostat-def': differs in spec.schema"
+        );
+    }
 }
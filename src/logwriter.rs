@@ -0,0 +1,237 @@
+//! Incremental stdout/stderr streaming for long-running processes.
+//!
+//! `set_output` is only ever called once, right before `close`, so a
+//! long-running function produces no visible progress and a crash loses
+//! everything it printed. [`LogWriter`] is a `tokio::io::AsyncWrite` that
+//! batches written bytes in memory and flushes them to the server via
+//! `add_log` on a timer, so a handler (or a piped child process) can stream
+//! output incrementally instead of buffering it all until the end.
+//! [`crate::stream::follow_logs`] is the reader-side counterpart for
+//! tailing it back out in real time.
+
+use crate::core::{colony_date_epoch, Log};
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context as TaskContext, Poll};
+use std::time::Duration;
+use tokio::io::AsyncWrite;
+
+/// Default interval on which buffered output is flushed to the server.
+pub const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+/// Default buffered size (bytes) past which a write triggers an immediate
+/// flush instead of waiting for the next tick.
+pub const DEFAULT_FLUSH_THRESHOLD: usize = 8192;
+
+struct Shared {
+    buf: Mutex<Vec<u8>>,
+    flush_threshold: usize,
+    processid: String,
+    colonyname: String,
+    executorname: String,
+    prvkey: String,
+}
+
+/// An `AsyncWrite` that batches written bytes and ships them to the server
+/// as `Log`s (one per line) instead of writing through synchronously.
+///
+/// Cloning is cheap (it shares the same buffer and background flush task);
+/// [`open_log_stream`] uses this to hand stdout and stderr their own handle
+/// onto the same stream. The background flush task exits once every clone
+/// has been dropped; any bytes still buffered at that point are lost, so a
+/// caller that cares about the trailing partial line should call
+/// [`LogWriter::flush_now`] before dropping its last handle.
+#[derive(Clone)]
+pub struct LogWriter {
+    shared: Arc<Shared>,
+}
+
+impl LogWriter {
+    /// Forces an immediate flush of whatever is currently buffered,
+    /// regardless of the flush interval or threshold.
+    pub async fn flush_now(&self) {
+        flush(&self.shared).await;
+    }
+}
+
+impl AsyncWrite for LogWriter {
+    fn poll_write(self: Pin<&mut Self>, _cx: &mut TaskContext<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let should_flush = {
+            let mut b = self.shared.buf.lock().unwrap();
+            b.extend_from_slice(buf);
+            b.len() >= self.shared.flush_threshold
+        };
+        if should_flush {
+            let shared = self.shared.clone();
+            tokio::spawn(async move { flush(&shared).await });
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut TaskContext<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Drains the buffer and ships each line as its own `add_log` call. A
+/// partial line (no trailing newline yet) stays buffered until the next
+/// flush completes it.
+async fn flush(shared: &Shared) {
+    let chunk = {
+        let mut b = shared.buf.lock().unwrap();
+        if b.is_empty() {
+            return;
+        }
+        // Keep a trailing partial line (no newline yet) buffered rather
+        // than shipping a half-written line early.
+        let split_at = match b.iter().rposition(|&byte| byte == b'\n') {
+            Some(pos) => pos + 1,
+            None => return,
+        };
+        b.drain(..split_at).collect::<Vec<u8>>()
+    };
+
+    let text = String::from_utf8_lossy(&chunk);
+    for line in text.lines() {
+        if line.is_empty() {
+            continue;
+        }
+        let log = Log {
+            processid: shared.processid.clone(),
+            colonyname: shared.colonyname.clone(),
+            executorname: shared.executorname.clone(),
+            message: line.to_owned(),
+            timestamp: colony_date_epoch(),
+        };
+        let _ = crate::add_log(&log, &shared.prvkey).await;
+    }
+}
+
+/// Opens a [`LogWriter`] for `processid`, flushing to the server every
+/// [`DEFAULT_FLUSH_INTERVAL`] or whenever [`DEFAULT_FLUSH_THRESHOLD`] bytes
+/// accumulate, whichever comes first.
+pub fn open_log_stream(processid: &str, executorname: &str, colonyname: &str, prvkey: &str) -> LogWriter {
+    open_log_stream_with(
+        processid,
+        executorname,
+        colonyname,
+        prvkey,
+        DEFAULT_FLUSH_INTERVAL,
+        DEFAULT_FLUSH_THRESHOLD,
+    )
+}
+
+/// Same as [`open_log_stream`], with an explicit flush interval/threshold.
+pub fn open_log_stream_with(
+    processid: &str,
+    executorname: &str,
+    colonyname: &str,
+    prvkey: &str,
+    flush_interval: Duration,
+    flush_threshold: usize,
+) -> LogWriter {
+    let shared = Arc::new(Shared {
+        buf: Mutex::new(Vec::new()),
+        flush_threshold,
+        processid: processid.to_owned(),
+        colonyname: colonyname.to_owned(),
+        executorname: executorname.to_owned(),
+        prvkey: prvkey.to_owned(),
+    });
+
+    // Holds only a `Weak` ref: once every `LogWriter` clone is dropped, the
+    // next tick's `upgrade()` fails and this loop exits instead of running
+    // forever.
+    let weak = Arc::downgrade(&shared);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(flush_interval);
+        loop {
+            ticker.tick().await;
+            match weak.upgrade() {
+                Some(shared) => flush(&shared).await,
+                None => break,
+            }
+        }
+    });
+
+    LogWriter { shared }
+}
+
+/// Pipes a spawned child's stdout/stderr into `writer` line by line until
+/// the child exits, so an executor gets live logs for free instead of
+/// buffering the child's output until it finishes. The child must have been
+/// spawned with `Stdio::piped()` for both streams.
+pub async fn pipe_child_into_log_stream(
+    child: &mut tokio::process::Child,
+    writer: LogWriter,
+) -> io::Result<std::process::ExitStatus> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut stdout = child.stdout.take().expect("child spawned without piped stdout");
+    let mut stderr = child.stderr.take().expect("child spawned without piped stderr");
+
+    let mut stdout_writer = writer.clone();
+    let mut stderr_writer = writer.clone();
+    let stdout_task = tokio::spawn(async move { tokio::io::copy(&mut stdout, &mut stdout_writer).await });
+    let stderr_task = tokio::spawn(async move { tokio::io::copy(&mut stderr, &mut stderr_writer).await });
+
+    let status = child.wait().await?;
+    let _ = stdout_task.await;
+    let _ = stderr_task.await;
+    writer.flush_now().await;
+
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test]
+    async fn test_poll_write_buffers_without_blocking() {
+        let writer = open_log_stream_with(
+            "process-123",
+            "exec",
+            "mycolony",
+            "prvkey",
+            Duration::from_secs(3600),
+            DEFAULT_FLUSH_THRESHOLD,
+        );
+        let mut writer = writer;
+        writer.write_all(b"hello world\n").await.unwrap();
+        assert_eq!(writer.shared.buf.lock().unwrap().as_slice(), b"hello world\n");
+    }
+
+    #[tokio::test]
+    async fn test_flush_keeps_partial_line_buffered() {
+        let shared = Arc::new(Shared {
+            buf: Mutex::new(b"complete line\npartial".to_vec()),
+            flush_threshold: DEFAULT_FLUSH_THRESHOLD,
+            processid: "process-123".to_owned(),
+            colonyname: "mycolony".to_owned(),
+            executorname: "exec".to_owned(),
+            prvkey: "prvkey".to_owned(),
+        });
+        flush(&shared).await;
+        assert_eq!(shared.buf.lock().unwrap().as_slice(), b"partial");
+    }
+
+    #[tokio::test]
+    async fn test_flush_noop_on_empty_buffer() {
+        let shared = Shared {
+            buf: Mutex::new(Vec::new()),
+            flush_threshold: DEFAULT_FLUSH_THRESHOLD,
+            processid: "process-123".to_owned(),
+            colonyname: "mycolony".to_owned(),
+            executorname: "exec".to_owned(),
+            prvkey: "prvkey".to_owned(),
+        };
+        flush(&shared).await;
+        assert!(shared.buf.lock().unwrap().is_empty());
+    }
+}
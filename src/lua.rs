@@ -0,0 +1,163 @@
+//! Lua-scripted function handlers for executors.
+//!
+//! Lets operators add new functions without recompiling: scripts are
+//! loaded from a directory keyed by `funcname`, and when a matching
+//! process is assigned, the script runs in a fresh `mlua` VM with host
+//! bindings mirroring the SDK (`args()`, `env(key)`, `set_output(table)`,
+//! `add_attribute(key, value)`, `fail(msg)`). This generalizes the
+//! hardcoded `echo`/`add`/`multiply` handlers in the examples into
+//! user-supplied logic.
+
+use crate::core::Process;
+use crate::executor::ProcessError;
+use mlua::{Lua, MultiValue, Value as LuaValue};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// A directory of `<funcname>.lua` scripts, loaded lazily on dispatch.
+pub struct LuaScripts {
+    dir: PathBuf,
+}
+
+impl LuaScripts {
+    pub fn new(dir: impl AsRef<Path>) -> LuaScripts {
+        LuaScripts {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn script_path(&self, funcname: &str) -> PathBuf {
+        self.dir.join(format!("{funcname}.lua"))
+    }
+
+    /// Returns true when a script exists for `funcname`.
+    pub fn has(&self, funcname: &str) -> bool {
+        self.script_path(funcname).is_file()
+    }
+
+    /// Runs the script bound to `process.spec.funcname` in a fresh Lua VM,
+    /// enforcing `spec.maxexectime` as a wall-clock timeout. Returns the
+    /// values passed to `set_output`.
+    pub async fn run(&self, process: Process) -> Result<Vec<String>, ProcessError> {
+        let path = self.script_path(&process.spec.funcname);
+        let source = std::fs::read_to_string(&path)
+            .map_err(|e| ProcessError::new(&format!("no script for {}: {}", process.spec.funcname, e)))?;
+
+        let timeout = if process.spec.maxexectime > 0 {
+            Duration::from_secs(process.spec.maxexectime as u64)
+        } else {
+            Duration::from_secs(60)
+        };
+
+        let task = tokio::task::spawn_blocking(move || run_script(&source, &process));
+
+        match tokio::time::timeout(timeout, task).await {
+            Ok(Ok(inner)) => inner,
+            Ok(Err(e)) => Err(ProcessError::new(&format!("script task panicked: {e}"))),
+            Err(_) => Err(ProcessError::new("script execution timed out")),
+        }
+    }
+}
+
+/// Shared outcome collected by the host bindings while a script runs.
+#[derive(Default)]
+struct ScriptOutcome {
+    output: Vec<String>,
+    attributes: Vec<(String, String)>,
+    failure: Option<String>,
+}
+
+fn run_script(source: &str, process: &Process) -> Result<Vec<String>, ProcessError> {
+    let lua = Lua::new();
+    let outcome = std::rc::Rc::new(std::cell::RefCell::new(ScriptOutcome::default()));
+
+    bind_host_functions(&lua, process, outcome.clone())
+        .map_err(|e| ProcessError::new(&format!("failed to bind host functions: {e}")))?;
+
+    lua.load(source)
+        .exec()
+        .map_err(|e| ProcessError::new(&format!("lua error: {e}")))?;
+
+    let outcome = outcome.borrow();
+    if let Some(msg) = &outcome.failure {
+        return Err(ProcessError::new(msg));
+    }
+    Ok(outcome.output.clone())
+}
+
+fn bind_host_functions(
+    lua: &Lua,
+    process: &Process,
+    outcome: std::rc::Rc<std::cell::RefCell<ScriptOutcome>>,
+) -> mlua::Result<()> {
+    let globals = lua.globals();
+
+    let args = process.spec.args.clone();
+    globals.set("args", lua.create_function(move |_, ()| Ok(args.clone()))?)?;
+
+    let env: HashMap<String, String> = process.spec.env.clone();
+    globals.set(
+        "env",
+        lua.create_function(move |_, key: String| Ok(env.get(&key).cloned()))?,
+    )?;
+
+    let out_sink = outcome.clone();
+    globals.set(
+        "set_output",
+        lua.create_function(move |_, values: MultiValue| {
+            let mut out = out_sink.borrow_mut();
+            for v in values {
+                out.output.push(lua_value_to_string(&v));
+            }
+            Ok(())
+        })?,
+    )?;
+
+    let attr_sink = outcome.clone();
+    globals.set(
+        "add_attribute",
+        lua.create_function(move |_, (key, value): (String, String)| {
+            attr_sink.borrow_mut().attributes.push((key, value));
+            Ok(())
+        })?,
+    )?;
+
+    let fail_sink = outcome;
+    globals.set(
+        "fail",
+        lua.create_function(move |_, msg: String| {
+            fail_sink.borrow_mut().failure = Some(msg);
+            Ok(())
+        })?,
+    )?;
+
+    Ok(())
+}
+
+fn lua_value_to_string(value: &LuaValue) -> String {
+    match value {
+        LuaValue::String(s) => s.to_str().map(|s| s.to_string()).unwrap_or_default(),
+        LuaValue::Integer(i) => i.to_string(),
+        LuaValue::Number(n) => n.to_string(),
+        LuaValue::Boolean(b) => b.to_string(),
+        _ => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_path() {
+        let scripts = LuaScripts::new("/tmp/scripts");
+        assert_eq!(scripts.script_path("echo"), PathBuf::from("/tmp/scripts/echo.lua"));
+    }
+
+    #[test]
+    fn test_has_missing_script() {
+        let scripts = LuaScripts::new("/tmp/does-not-exist-colonyos");
+        assert!(!scripts.has("echo"));
+    }
+}
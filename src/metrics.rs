@@ -0,0 +1,154 @@
+//! Metrics export for `Function` timing statistics.
+//!
+//! `Function` already aggregates `counter` and min/max/avg wait and exec
+//! times (`test_function_with_timing_stats`), which is exactly what a
+//! dashboard wants but there was no way to get it out of the SDK. This
+//! serializes a slice of `Function`s into InfluxDB line protocol
+//! (measurement `colony_function`) and Prometheus text exposition format,
+//! so operators can scrape or push per-function latency stats into
+//! Grafana without custom glue.
+
+use crate::core::Function;
+
+const FIELDS: [(&str, fn(&Function) -> f64); 7] = [
+    ("counter", |f| f.counter as f64),
+    ("minwaittime", |f| f.minwaittime),
+    ("maxwaittime", |f| f.maxwaittime),
+    ("minexectime", |f| f.minexectime),
+    ("maxexectime", |f| f.maxexectime),
+    ("avgwaittime", |f| f.avgwaittime),
+    ("avgexectime", |f| f.avgexectime),
+];
+
+fn escape_tag(value: &str) -> String {
+    value.replace(' ', "\\ ").replace(',', "\\,").replace('=', "\\=")
+}
+
+fn sanitize_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+impl Function {
+    /// Serializes this function's timing stats as a single InfluxDB line
+    /// protocol record, tagged by `colonyname`/`executortype`/`funcname`.
+    pub fn to_influx_line(&self) -> String {
+        let tags = format!(
+            "colonyname={},executortype={},funcname={}",
+            escape_tag(&self.colonyname),
+            escape_tag(&self.executortype),
+            escape_tag(&self.funcname)
+        );
+
+        let fields = FIELDS
+            .iter()
+            .map(|(name, get)| format!("{name}={}", get(self)))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        format!("colony_function,{tags} {fields}")
+    }
+
+    /// Serializes this function's timing stats as Prometheus gauge samples,
+    /// one line per field, labeled by `colonyname`/`executortype`/
+    /// `funcname`. Callers combining multiple functions should emit the
+    /// `# HELP`/`# TYPE` header once via [`prometheus_header`].
+    pub fn to_prometheus(&self) -> String {
+        let labels = format!(
+            r#"colonyname="{}",executortype="{}",funcname="{}""#,
+            sanitize_label(&self.colonyname),
+            sanitize_label(&self.executortype),
+            sanitize_label(&self.funcname)
+        );
+
+        FIELDS
+            .iter()
+            .map(|(name, get)| format!("colony_function_{name}{{{labels}}} {}", get(self)))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Serializes `functions` as a batch of InfluxDB line protocol records, one
+/// per line.
+pub fn to_influx_lines(functions: &[Function]) -> String {
+    functions
+        .iter()
+        .map(Function::to_influx_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Serializes `functions` as a full Prometheus text exposition document,
+/// including the `# HELP`/`# TYPE` header for each stat.
+pub fn to_prometheus_text(functions: &[Function]) -> String {
+    let mut out = String::new();
+    for (name, _) in FIELDS {
+        out.push_str(&format!("# HELP colony_function_{name} ColonyOS function {name}\n"));
+        out.push_str(&format!("# TYPE colony_function_{name} gauge\n"));
+    }
+    out.push_str(
+        &functions
+            .iter()
+            .map(Function::to_prometheus)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_function() -> Function {
+        Function {
+            functionid: "func-123".to_owned(),
+            executorname: "worker".to_owned(),
+            executortype: "cli".to_owned(),
+            colonyname: "colony".to_owned(),
+            funcname: "process".to_owned(),
+            counter: 1000,
+            minwaittime: 0.1,
+            maxwaittime: 10.5,
+            minexectime: 1.0,
+            maxexectime: 60.0,
+            avgwaittime: 2.5,
+            avgexectime: 15.0,
+        }
+    }
+
+    #[test]
+    fn test_to_influx_line_includes_tags_and_fields() {
+        let line = sample_function().to_influx_line();
+        assert!(line.starts_with("colony_function,colonyname=colony,executortype=cli,funcname=process "));
+        assert!(line.contains("counter=1000"));
+        assert!(line.contains("avgexectime=15"));
+    }
+
+    #[test]
+    fn test_to_prometheus_includes_labels_for_every_field() {
+        let text = sample_function().to_prometheus();
+        assert!(text.contains(r#"colony_function_counter{colonyname="colony",executortype="cli",funcname="process"} 1000"#));
+        assert!(text.contains("colony_function_avgwaittime"));
+    }
+
+    #[test]
+    fn test_to_influx_lines_batches_multiple_functions() {
+        let mut other = sample_function();
+        other.funcname = "render".to_owned();
+        let batch = to_influx_lines(&[sample_function(), other]);
+        assert_eq!(batch.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_to_prometheus_text_includes_help_and_type_header() {
+        let text = to_prometheus_text(&[sample_function()]);
+        assert!(text.contains("# HELP colony_function_counter"));
+        assert!(text.contains("# TYPE colony_function_counter gauge"));
+    }
+
+    #[test]
+    fn test_escape_tag_handles_reserved_characters() {
+        assert_eq!(escape_tag("a b,c=d"), "a\\ b\\,c\\=d");
+    }
+}
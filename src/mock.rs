@@ -0,0 +1,388 @@
+//! In-process mock transport for hermetic process/channel tests.
+//!
+//! `tests/integration_test.rs` hardcodes `SERVER_PRVKEY` and talks to a
+//! live ColonyOS server for every test, with "if the server doesn't
+//! support channels, the test passes anyway" escape hatches sprinkled
+//! through the channel tests — the whole suite is both useless without a
+//! server and, even with one, can silently no-op instead of asserting
+//! anything. [`MockServer`] keeps colonies, executors, processes,
+//! attributes, logs, and channel entries in memory — including real
+//! per-channel sequence assignment and `afterseq` filtering — so a test
+//! can drive the same `submit`/`assign`/`close`/`channel_append`/
+//! `channel_read`/`subscribe_channel` calls and assert exact ordering with
+//! no server and no "maybe this isn't supported" branch.
+//!
+//! [`install`] makes a `MockServer` the active transport for the calling
+//! thread's tests: the handful of `crate::*` functions that matter for
+//! channel-centric tests ([`crate::submit`], [`crate::assign`],
+//! [`crate::close`], [`crate::channel_append`], [`crate::channel_read`],
+//! [`crate::subscribe_channel`], [`crate::add_colony`],
+//! [`crate::add_executor`], [`crate::get_process`]) check for an active
+//! mock before making a real RPC call. Wider surface area (blueprints, the
+//! reconciler, CAS, artifacts, ...) isn't modeled; this covers the
+//! process/channel primitives the channel tests in this chunk actually
+//! exercise, not the whole RPC surface.
+
+use crate::core::{
+    Attribute, ChannelEntry, Colony, Executor, ExecutorState, FunctionSpec, Log, Process, ProcessState, CONTENT_TYPE_TEXT,
+};
+use crate::rpc::RPCError;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+#[derive(Default)]
+struct State {
+    colonies: HashMap<String, Colony>,
+    executors: HashMap<(String, String), Executor>,
+    processes: HashMap<String, Process>,
+    attributes: HashMap<String, Attribute>,
+    logs: HashMap<String, Vec<Log>>,
+    channels: HashMap<(String, String), Vec<ChannelEntry>>,
+    /// Monotonic sequence number assigned to each process at `submit`
+    /// time, keyed by `processid`. `processid` itself is a SHA3 hash of a
+    /// random keypair, so it carries no submission-order information;
+    /// `assign` needs this to actually pick the oldest waiting process.
+    submission_seq: HashMap<String, u64>,
+    next_submission_seq: u64,
+}
+
+/// An in-memory stand-in for a ColonyOS server, covering the primitives
+/// channel-centric tests exercise. See the module docs for what's in
+/// scope.
+#[derive(Default)]
+pub struct MockServer {
+    state: Mutex<State>,
+    notify: Notify,
+}
+
+impl MockServer {
+    pub fn new() -> Arc<MockServer> {
+        Arc::new(MockServer::default())
+    }
+
+    pub fn add_colony(&self, colony: &Colony) -> Result<Colony, RPCError> {
+        let mut state = self.state.lock().unwrap();
+        state.colonies.insert(colony.name.clone(), colony.clone());
+        Ok(colony.clone())
+    }
+
+    pub fn remove_colony(&self, colonyname: &str) -> Result<(), RPCError> {
+        let mut state = self.state.lock().unwrap();
+        state.colonies.remove(colonyname);
+        state.processes.retain(|_, p| p.spec.conditions.colonyname != colonyname);
+        Ok(())
+    }
+
+    pub fn add_executor(&self, executor: &Executor) -> Result<Executor, RPCError> {
+        let mut state = self.state.lock().unwrap();
+        let mut executor = executor.clone();
+        executor.state = ExecutorState::Approved;
+        state
+            .executors
+            .insert((executor.colonyname.clone(), executor.executorname.clone()), executor.clone());
+        Ok(executor)
+    }
+
+    pub fn submit(&self, spec: &FunctionSpec) -> Result<Process, RPCError> {
+        let mut state = self.state.lock().unwrap();
+        if !state.colonies.contains_key(&spec.conditions.colonyname) {
+            return Err(RPCError::new(&format!("colony {} does not exist", spec.conditions.colonyname), false));
+        }
+        let processid = crate::crypto::gen_id(&crate::crypto::gen_prvkey());
+        let process = Process {
+            processid: processid.clone(),
+            initiatorid: String::new(),
+            initiatorname: String::new(),
+            assignedexecutorid: String::new(),
+            isassigned: false,
+            state: ProcessState::Waiting,
+            prioritytime: 0,
+            submissiontime: crate::core::colony_date_epoch(),
+            starttime: crate::core::colony_date_epoch(),
+            endtime: crate::core::colony_date_epoch(),
+            waitdeadline: crate::core::colony_date_epoch(),
+            execdeadline: crate::core::colony_date_epoch(),
+            retries: 0,
+            attributes: Vec::new(),
+            spec: spec.clone(),
+            waitforparents: false,
+            parents: Vec::new(),
+            children: Vec::new(),
+            processgraphid: String::new(),
+            input: Vec::new(),
+            output: Vec::new(),
+            errors: Vec::new(),
+        };
+        let seq = state.next_submission_seq;
+        state.next_submission_seq += 1;
+        state.submission_seq.insert(processid.clone(), seq);
+        state.processes.insert(processid, process.clone());
+        Ok(process)
+    }
+
+    /// Assigns the oldest still-`Waiting` process in `colonyname` to an
+    /// executor, mirroring the real server's FIFO scheduling closely
+    /// enough for tests: order of `submit` calls is the order processes
+    /// become assignable.
+    pub fn assign(&self, colonyname: &str) -> Result<Process, RPCError> {
+        let mut state = self.state.lock().unwrap();
+        let submission_seq = &state.submission_seq;
+        let processid = state
+            .processes
+            .values()
+            .filter(|p| p.spec.conditions.colonyname == colonyname && p.state == ProcessState::Waiting)
+            .min_by_key(|p| submission_seq.get(&p.processid).copied().unwrap_or(u64::MAX))
+            .map(|p| p.processid.clone());
+        let Some(processid) = processid else {
+            return Err(RPCError::new("no waiting process available", false));
+        };
+        let process = state.processes.get_mut(&processid).unwrap();
+        process.state = ProcessState::Running;
+        process.isassigned = true;
+        Ok(process.clone())
+    }
+
+    pub fn close(&self, processid: &str) -> Result<(), RPCError> {
+        let mut state = self.state.lock().unwrap();
+        let process = state
+            .processes
+            .get_mut(processid)
+            .ok_or_else(|| RPCError::new(&format!("process {processid} not found"), false))?;
+        process.state = ProcessState::Success;
+        Ok(())
+    }
+
+    pub fn get_process(&self, processid: &str) -> Result<Process, RPCError> {
+        let state = self.state.lock().unwrap();
+        state
+            .processes
+            .get(processid)
+            .cloned()
+            .ok_or_else(|| RPCError::new(&format!("process {processid} not found"), false))
+    }
+
+    pub fn add_attr(&self, attr: &Attribute) -> Result<Attribute, RPCError> {
+        let mut state = self.state.lock().unwrap();
+        let mut attr = attr.clone();
+        attr.attributeid = crate::crypto::gen_id(&crate::crypto::gen_prvkey());
+        state.attributes.insert(attr.attributeid.clone(), attr.clone());
+        Ok(attr)
+    }
+
+    pub fn add_log(&self, log: &Log) -> Result<(), RPCError> {
+        let mut state = self.state.lock().unwrap();
+        state.logs.entry(log.processid.clone()).or_default().push(log.clone());
+        Ok(())
+    }
+
+    pub fn get_logs(&self, processid: &str) -> Result<Vec<Log>, RPCError> {
+        let state = self.state.lock().unwrap();
+        Ok(state.logs.get(processid).cloned().unwrap_or_default())
+    }
+
+    /// Appends `data` to `channelname`, assigning the next sequence number
+    /// for that `(processid, channelname)` pair itself (the incoming
+    /// `sequence` is only used for `inreplyto` correlation by callers, the
+    /// same way the real server is the source of truth for ordering), and
+    /// wakes any `subscribe_channel` calls waiting on it.
+    pub fn channel_append(
+        &self,
+        processid: &str,
+        channelname: &str,
+        data: &[u8],
+        content_type: &str,
+        inreplyto: i64,
+    ) -> Result<ChannelEntry, RPCError> {
+        use base64::{engine::general_purpose::STANDARD, Engine as _};
+        let mut state = self.state.lock().unwrap();
+        let key = (processid.to_owned(), channelname.to_owned());
+        let entries = state.channels.entry(key).or_default();
+        let sequence = entries.last().map(|e| e.sequence).unwrap_or(0) + 1;
+        let entry = ChannelEntry {
+            sequence,
+            payload: STANDARD.encode(data),
+            msgtype: String::new(),
+            inreplyto,
+            timestamp: crate::core::colony_date_epoch(),
+            senderid: String::new(),
+            contenttype: content_type.to_owned(),
+        };
+        entries.push(entry.clone());
+        drop(state);
+        self.notify.notify_waiters();
+        Ok(entry)
+    }
+
+    /// Returns entries with `sequence > afterseq` (and, if `content_type`
+    /// is non-empty, matching `contenttype`), oldest first, up to `count`
+    /// (0 or negative means unlimited).
+    pub fn channel_read(
+        &self,
+        processid: &str,
+        channelname: &str,
+        afterseq: i64,
+        count: i32,
+        content_type: &str,
+    ) -> Result<Vec<ChannelEntry>, RPCError> {
+        let state = self.state.lock().unwrap();
+        let key = (processid.to_owned(), channelname.to_owned());
+        let mut matching: Vec<ChannelEntry> = state
+            .channels
+            .get(&key)
+            .into_iter()
+            .flatten()
+            .filter(|e| e.sequence > afterseq)
+            .filter(|e| content_type.is_empty() || e.contenttype == content_type)
+            .cloned()
+            .collect();
+        if count > 0 {
+            matching.truncate(count as usize);
+        }
+        Ok(matching)
+    }
+
+    /// Long-polls `channelname` for entries after `afterseq`, invoking
+    /// `callback` with each newly observed batch until it returns `false`
+    /// or `timeout` elapses with nothing new.
+    pub async fn subscribe_channel<F>(
+        &self,
+        processid: &str,
+        channelname: &str,
+        afterseq: i64,
+        timeout: i32,
+        mut callback: F,
+    ) -> Result<Vec<ChannelEntry>, RPCError>
+    where
+        F: FnMut(Vec<ChannelEntry>) -> bool,
+    {
+        let mut since = afterseq;
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(timeout.max(0) as u64);
+        loop {
+            let batch = self.channel_read(processid, channelname, since, 0, "")?;
+            if !batch.is_empty() {
+                since = batch.last().map(|e| e.sequence).unwrap_or(since);
+                if !callback(batch) {
+                    return Ok(Vec::new());
+                }
+                continue;
+            }
+            let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+            if remaining.is_zero() {
+                return Ok(Vec::new());
+            }
+            let _ = tokio::time::timeout(remaining, self.notify.notified()).await;
+            if tokio::time::Instant::now() >= deadline {
+                return Ok(Vec::new());
+            }
+        }
+    }
+}
+
+thread_local! {
+    static ACTIVE: std::cell::RefCell<Option<Arc<MockServer>>> = const { std::cell::RefCell::new(None) };
+}
+
+/// Makes `server` the active transport for `crate::*`'s process/channel
+/// functions on the calling thread, until [`uninstall`] is called. Tests
+/// that span multiple threads (e.g. `tokio::spawn`) must `install` again
+/// on each one, since the override is thread-local rather than global.
+pub fn install(server: Arc<MockServer>) {
+    ACTIVE.with(|cell| *cell.borrow_mut() = Some(server));
+}
+
+/// Clears the active mock transport set by [`install`].
+pub fn uninstall() {
+    ACTIVE.with(|cell| *cell.borrow_mut() = None);
+}
+
+/// Returns the thread's active mock transport, if any.
+pub(crate) fn active() -> Option<Arc<MockServer>> {
+    ACTIVE.with(|cell| cell.borrow().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(colonyname: &str) -> FunctionSpec {
+        FunctionSpec::new("echo", "cli", colonyname)
+    }
+
+    #[test]
+    fn test_submit_requires_existing_colony() {
+        let server = MockServer::default();
+        let err = server.submit(&spec("nope")).unwrap_err();
+        assert!(err.to_string().contains("does not exist"));
+    }
+
+    #[test]
+    fn test_assign_picks_oldest_waiting_process() {
+        let server = MockServer::default();
+        server.add_colony(&Colony::new("colony-1", "mycolony")).unwrap();
+        let p1 = server.submit(&spec("mycolony")).unwrap();
+        let assigned = server.assign("mycolony").unwrap();
+        assert_eq!(assigned.processid, p1.processid);
+        assert_eq!(assigned.state, ProcessState::Running);
+    }
+
+    #[test]
+    fn test_assign_picks_oldest_among_several_waiting_processes() {
+        let server = MockServer::default();
+        server.add_colony(&Colony::new("colony-1", "mycolony")).unwrap();
+        let p1 = server.submit(&spec("mycolony")).unwrap();
+        let _p2 = server.submit(&spec("mycolony")).unwrap();
+        let _p3 = server.submit(&spec("mycolony")).unwrap();
+
+        assert_eq!(server.assign("mycolony").unwrap().processid, p1.processid);
+    }
+
+    #[test]
+    fn test_channel_append_assigns_increasing_sequence() {
+        let server = MockServer::default();
+        let e1 = server.channel_append("proc-1", "chan", b"a", CONTENT_TYPE_TEXT, 0).unwrap();
+        let e2 = server.channel_append("proc-1", "chan", b"b", CONTENT_TYPE_TEXT, 0).unwrap();
+        assert_eq!(e1.sequence, 1);
+        assert_eq!(e2.sequence, 2);
+    }
+
+    #[test]
+    fn test_channel_read_filters_by_afterseq() {
+        let server = MockServer::default();
+        server.channel_append("proc-1", "chan", b"a", CONTENT_TYPE_TEXT, 0).unwrap();
+        server.channel_append("proc-1", "chan", b"b", CONTENT_TYPE_TEXT, 0).unwrap();
+        let entries = server.channel_read("proc-1", "chan", 1, 0, "").unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].payload_as_string(), "b");
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_channel_delivers_backlog_then_stops() {
+        let server = MockServer::default();
+        server.channel_append("proc-1", "chan", b"a", CONTENT_TYPE_TEXT, 0).unwrap();
+        server.channel_append("proc-1", "chan", b"b", CONTENT_TYPE_TEXT, 0).unwrap();
+
+        let mut seen = Vec::new();
+        server
+            .subscribe_channel("proc-1", "chan", 0, 1, |entries| {
+                for e in entries {
+                    seen.push(e.payload_as_string());
+                }
+                seen.len() < 2
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(seen, vec!["a".to_owned(), "b".to_owned()]);
+    }
+
+    #[tokio::test]
+    async fn test_install_and_uninstall_scope_to_thread() {
+        assert!(active().is_none());
+        install(MockServer::new());
+        assert!(active().is_some());
+        uninstall();
+        assert!(active().is_none());
+    }
+}
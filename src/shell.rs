@@ -0,0 +1,247 @@
+//! Interactive remote shell sessions built on process channels.
+//!
+//! [`exec::run`](crate::exec::run) batches a whole command to one
+//! submit/assign/close round trip; there's no way to drive a long-running
+//! interactive command a keystroke at a time. `open_shell` gives a client a
+//! [`ShellSession`] over a process channel, and `pump_shell` is the
+//! executor-side counterpart that pipes a spawned child's stdout/stderr
+//! into channel appends and channel entries back into the child's stdin,
+//! so a remote shell behaves like the `ssh`/`kubectl exec` tooling this is
+//! modeled on. Messages are tagged [`ShellMessage`] variants rather than
+//! plain bytes: the channel wire format has no settable per-entry type tag
+//! of its own (`ChannelEntry::msgtype` is populated server-side, not by the
+//! appender), so the tag travels inside the JSON payload itself, the same
+//! way [`crate::core::ChannelEntry::decode`] already expects a
+//! self-describing typed payload.
+//!
+//! Stdio is piped, not a real PTY (same as [`crate::exec::run`]), so
+//! [`ShellMessage::Resize`] is relayed but has no effect on the child's
+//! terminal geometry.
+
+use crate::core::CONTENT_TYPE_JSON;
+use crate::executor::ProcessError;
+use crate::rpc::RPCError;
+use crate::stream::ChannelSubscription;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+
+const READ_CHUNK_SIZE: usize = 4096;
+
+/// One message exchanged over a shell channel.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum ShellMessage {
+    Stdin { data: Vec<u8> },
+    Stdout { data: Vec<u8> },
+    Stderr { data: Vec<u8> },
+    Resize { rows: u16, cols: u16 },
+    Exit { code: i32 },
+}
+
+/// Client-side handle to an interactive shell running as `processid`,
+/// exchanging [`ShellMessage`]s over `channel`. Returned by [`open_shell`].
+pub struct ShellSession {
+    processid: String,
+    channel: String,
+    prvkey: String,
+    sequence: i64,
+    sub: ChannelSubscription,
+}
+
+impl ShellSession {
+    /// Sends `bytes` to the child's stdin.
+    pub async fn write_stdin(&mut self, bytes: &[u8]) -> Result<(), RPCError> {
+        self.append(ShellMessage::Stdin { data: bytes.to_vec() }).await
+    }
+
+    /// Notifies the executor side of a terminal resize.
+    pub async fn resize(&mut self, rows: u16, cols: u16) -> Result<(), RPCError> {
+        self.append(ShellMessage::Resize { rows, cols }).await
+    }
+
+    /// Waits for the next stdout/stderr chunk or the terminal `Exit`
+    /// message, or `None` once the underlying subscription ends.
+    pub async fn recv(&mut self) -> Option<ShellMessage> {
+        loop {
+            let entry = self.sub.recv().await?;
+            if let Ok(msg) = entry.decode::<ShellMessage>() {
+                return Some(msg);
+            }
+            // Not a ShellMessage (e.g. a stray entry on the same channel);
+            // skip it and keep waiting.
+        }
+    }
+
+    async fn append(&mut self, msg: ShellMessage) -> Result<(), RPCError> {
+        self.sequence += 1;
+        let json = serde_json::to_vec(&msg).expect("ShellMessage always serializes");
+        crate::channel_append_json(&self.processid, &self.channel, self.sequence, &json, 0, &self.prvkey).await?;
+        Ok(())
+    }
+}
+
+/// Opens a [`ShellSession`] against `channel` on `processid`, subscribing
+/// from the beginning so no output emitted before the session connects is
+/// missed.
+pub fn open_shell(processid: &str, channel: &str, prvkey: &str) -> ShellSession {
+    let sub = crate::stream::subscribe_channel_stream(processid, channel, 0, 30, prvkey);
+    ShellSession {
+        processid: processid.to_owned(),
+        channel: channel.to_owned(),
+        prvkey: prvkey.to_owned(),
+        sequence: 0,
+        sub,
+    }
+}
+
+/// Executor-side counterpart to [`open_shell`]: spawns `args` (via `sh -c`
+/// so the first element can be a full command line) with piped stdio,
+/// pumps its stdout/stderr into `Stdout`/`Stderr` appends on `channel`,
+/// applies incoming `Stdin` messages to the child's stdin, and appends a
+/// final `Exit` message carrying the status code once the child exits.
+/// Returns that same exit code.
+pub async fn pump_shell(args: &[String], processid: &str, channel: &str, prvkey: &str) -> Result<i32, ProcessError> {
+    if args.is_empty() {
+        return Err(ProcessError::new("shell: no command given"));
+    }
+
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(args.join(" "));
+    command.stdin(Stdio::piped());
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| ProcessError::new(&format!("shell: failed to start command: {e}")))?;
+
+    let stdin = child.stdin.take().expect("child spawned without piped stdin");
+    let stdout = child.stdout.take().expect("child spawned without piped stdout");
+    let stderr = child.stderr.take().expect("child spawned without piped stderr");
+
+    let mut appender = ShellAppender::new(processid, channel, prvkey);
+    let stdout_task = tokio::spawn(pump_output(stdout, appender.clone_for_task(), false));
+    let stderr_task = tokio::spawn(pump_output(stderr, appender.clone_for_task(), true));
+
+    let sub = crate::stream::subscribe_channel_stream(processid, channel, 0, 30, prvkey);
+    let input_task = tokio::spawn(pump_input(sub, stdin));
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| ProcessError::new(&format!("shell: failed to wait for command: {e}")))?;
+
+    input_task.abort();
+    let _ = stdout_task.await;
+    let _ = stderr_task.await;
+
+    let code = status.code().unwrap_or(-1);
+    appender.append(ShellMessage::Exit { code }).await.map_err(|e| {
+        ProcessError::new(&format!("shell: failed to append exit message: {e}"))
+    })?;
+    Ok(code)
+}
+
+/// Small helper bundling the destination of [`pump_shell`]'s appends with
+/// its own sequence counter, so each background task can append
+/// independently without sharing a `Mutex`.
+struct ShellAppender {
+    processid: String,
+    channel: String,
+    prvkey: String,
+    sequence: i64,
+}
+
+impl ShellAppender {
+    fn new(processid: &str, channel: &str, prvkey: &str) -> ShellAppender {
+        ShellAppender {
+            processid: processid.to_owned(),
+            channel: channel.to_owned(),
+            prvkey: prvkey.to_owned(),
+            sequence: 0,
+        }
+    }
+
+    /// Clones the destination for use in a separate task, starting that
+    /// task's own sequence counter from zero; stdout and stderr each get
+    /// their own append-ordering stream rather than sharing one.
+    fn clone_for_task(&self) -> ShellAppender {
+        ShellAppender::new(&self.processid, &self.channel, &self.prvkey)
+    }
+
+    async fn append(&mut self, msg: ShellMessage) -> Result<(), RPCError> {
+        self.sequence += 1;
+        let json = serde_json::to_vec(&msg).expect("ShellMessage always serializes");
+        crate::channel_append(
+            &self.processid,
+            &self.channel,
+            self.sequence,
+            &json,
+            CONTENT_TYPE_JSON,
+            0,
+            &self.prvkey,
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+async fn pump_output(mut pipe: impl tokio::io::AsyncRead + Unpin, mut appender: ShellAppender, is_stderr: bool) {
+    let mut buf = vec![0u8; READ_CHUNK_SIZE];
+    loop {
+        match pipe.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(n) => {
+                let data = buf[..n].to_vec();
+                let msg = if is_stderr { ShellMessage::Stderr { data } } else { ShellMessage::Stdout { data } };
+                if appender.append(msg).await.is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+async fn pump_input(mut sub: ChannelSubscription, mut stdin: tokio::process::ChildStdin) {
+    while let Some(entry) = sub.recv().await {
+        match entry.decode::<ShellMessage>() {
+            Ok(ShellMessage::Stdin { data }) => {
+                if stdin.write_all(&data).await.is_err() {
+                    return;
+                }
+            }
+            // Resize has no effect on plain piped stdio (no real PTY
+            // allocated); relayed for symmetry with the client API only.
+            Ok(ShellMessage::Resize { .. }) => {}
+            Ok(_) | Err(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_message_roundtrips_through_json() {
+        let msg = ShellMessage::Stdin { data: vec![1, 2, 3] };
+        let json = serde_json::to_vec(&msg).unwrap();
+        let decoded: ShellMessage = serde_json::from_slice(&json).unwrap();
+        assert_eq!(decoded, msg);
+    }
+
+    #[test]
+    fn test_shell_message_tags_are_lowercase() {
+        let msg = ShellMessage::Exit { code: 0 };
+        let json = serde_json::to_string(&msg).unwrap();
+        assert!(json.contains("\"kind\":\"exit\""), "{json}");
+    }
+
+    #[tokio::test]
+    async fn test_pump_shell_rejects_empty_args() {
+        let err = pump_shell(&[], "process-123", "shell", "prvkey").await.unwrap_err();
+        assert!(err.message.contains("no command given"));
+    }
+}
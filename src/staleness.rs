@@ -0,0 +1,167 @@
+//! Staleness detection and human-readable age for `Executor`.
+//!
+//! Only built with the `chrono` feature, since `Executor::commissiontime`/
+//! `lastheardfromtime` are `ColonyDate` (`chrono::DateTime<Utc>` under this
+//! feature, a plain RFC3339 `String` without it) and the age/staleness
+//! logic here is meaningless without real timestamps to do arithmetic on.
+//! This adds an `age()`/`is_stale()` pair for dead-executor reaping, a
+//! timeago-style `last_seen_humanized()` for status output, and a
+//! `parse_duration` helper so TTLs can be configured from plain strings
+//! like `"30s"`/`"5m"`.
+
+use crate::core::Executor;
+use chrono::{DateTime, Utc};
+use std::time::Duration;
+
+/// Returned when a duration string can't be parsed.
+#[derive(Debug, Clone)]
+pub struct TimeParseError {
+    pub message: String,
+}
+
+impl TimeParseError {
+    fn new(message: impl Into<String>) -> TimeParseError {
+        TimeParseError {
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for TimeParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Executor {
+    /// Returns `commissiontime`, already parsed since `ColonyDate` is
+    /// `chrono::DateTime<Utc>` under this feature.
+    pub fn commissioned_at(&self) -> DateTime<Utc> {
+        self.commissiontime
+    }
+
+    /// Returns `lastheardfromtime`, already parsed since `ColonyDate` is
+    /// `chrono::DateTime<Utc>` under this feature.
+    pub fn last_heard_from_at(&self) -> DateTime<Utc> {
+        self.lastheardfromtime
+    }
+
+    /// Time elapsed since the last heartbeat.
+    pub fn age(&self) -> Duration {
+        (Utc::now() - self.last_heard_from_at()).to_std().unwrap_or(Duration::ZERO)
+    }
+
+    /// True when this executor's heartbeat is older than `ttl`.
+    pub fn is_stale(&self, ttl: Duration) -> bool {
+        self.age() > ttl
+    }
+
+    /// Timeago-style rendering of [`Executor::age`], e.g. `"3 minutes ago"`.
+    pub fn last_seen_humanized(&self) -> String {
+        humanize_age(self.age())
+    }
+}
+
+/// Renders a duration as a coarse, human-friendly "N units ago" string.
+fn humanize_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    if secs < 5 {
+        return "just now".to_owned();
+    }
+
+    let (value, unit) = if secs < 60 {
+        (secs, "second")
+    } else if secs < 60 * 60 {
+        (secs / 60, "minute")
+    } else if secs < 60 * 60 * 24 {
+        (secs / (60 * 60), "hour")
+    } else {
+        (secs / (60 * 60 * 24), "day")
+    };
+
+    let plural = if value == 1 { "" } else { "s" };
+    format!("{value} {unit}{plural} ago")
+}
+
+/// Parses durations like `"30s"`, `"5m"`, `"2h"`, `"1d"`, or a bare number
+/// of seconds (`"30"`), for configuring staleness TTLs from config strings.
+pub fn parse_duration(s: &str) -> Result<Duration, TimeParseError> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(TimeParseError::new("duration string is empty"));
+    }
+
+    let split_at = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (digits, suffix) = s.split_at(split_at);
+
+    let value: u64 = digits
+        .parse()
+        .map_err(|_| TimeParseError::new(format!("invalid duration {s:?}: expected a leading number")))?;
+
+    let multiplier = match suffix {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 60 * 60 * 24,
+        other => {
+            return Err(TimeParseError::new(format!(
+                "invalid duration {s:?}: unknown unit {other:?}, expected s/m/h/d"
+            )))
+        }
+    };
+
+    Ok(Duration::from_secs(value * multiplier))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn executor_heard_from(lastheardfromtime: DateTime<Utc>) -> Executor {
+        let mut exec = Executor::new("worker-1", "exec-123", "docker", "production");
+        exec.commissiontime = DateTime::parse_from_rfc3339("2025-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        exec.lastheardfromtime = lastheardfromtime;
+        exec
+    }
+
+    #[test]
+    fn test_commissioned_at_returns_parsed_timestamp() {
+        let exec = executor_heard_from(Utc::now());
+        assert_eq!(exec.commissioned_at().to_rfc3339(), "2025-01-01T00:00:00+00:00");
+    }
+
+    #[test]
+    fn test_is_stale_respects_ttl() {
+        let recent = Utc::now() - chrono::Duration::seconds(5);
+        let exec = executor_heard_from(recent);
+        assert!(!exec.is_stale(Duration::from_secs(60)));
+        assert!(exec.is_stale(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_humanize_age_tiers() {
+        assert_eq!(humanize_age(Duration::from_secs(2)), "just now");
+        assert_eq!(humanize_age(Duration::from_secs(45)), "45 seconds ago");
+        assert_eq!(humanize_age(Duration::from_secs(180)), "3 minutes ago");
+        assert_eq!(humanize_age(Duration::from_secs(60)), "1 minute ago");
+        assert_eq!(humanize_age(Duration::from_secs(7200)), "2 hours ago");
+        assert_eq!(humanize_age(Duration::from_secs(172800)), "2 days ago");
+    }
+
+    #[test]
+    fn test_parse_duration_accepts_suffixes() {
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_duration("5m").unwrap(), Duration::from_secs(300));
+        assert_eq!(parse_duration("2h").unwrap(), Duration::from_secs(7200));
+        assert_eq!(parse_duration("1d").unwrap(), Duration::from_secs(86400));
+        assert_eq!(parse_duration("30").unwrap(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_unknown_unit() {
+        assert!(parse_duration("5x").is_err());
+        assert!(parse_duration("").is_err());
+    }
+}
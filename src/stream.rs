@@ -0,0 +1,651 @@
+//! Push-based alternatives to polling for process/workflow state and logs.
+//!
+//! Both the `submit_process` and `workflow` examples busy-poll with
+//! `sleep(500ms)`, which is wasteful and laggy. This module adds
+//! subscription APIs that yield a stream of updates instead, so callers can
+//! write `while let Some(item) = stream.next().await` in place of a poll
+//! loop.
+
+use crate::backoff::BackoffPolicy;
+use crate::core::{colony_date_as_millis, BlueprintEvent, ChannelEntry, Log, Process, WAITING};
+use crate::rpc::RPCError;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Subscribes to newly assignable processes in `colonyname` restricted to
+/// `executortype` (empty matches any executor type), pushing each one
+/// assigned to us instead of requiring an explicit `assign` retry loop. An
+/// empty `funcname_filter` matches every function name; otherwise only
+/// processes whose `spec.funcname` matches are yielded client-side.
+///
+/// Each notification is backed by a persistent WebSocket; transient
+/// connection errors are retried using [`BackoffPolicy`] so large colonies
+/// don't see a tight reconnect loop. The returned stream never ends on its
+/// own; drop it to stop subscribing.
+pub fn subscribe_processes(
+    colonyname: &str,
+    executortype: &str,
+    funcname_filter: &str,
+    prvkey: &str,
+) -> ReceiverStream<Process> {
+    let (tx, rx) = mpsc::channel(64);
+    let colonyname = colonyname.to_owned();
+    let executortype = executortype.to_owned();
+    let funcname_filter = funcname_filter.to_owned();
+    let prvkey = prvkey.to_owned();
+
+    crate::rt::spawn(async move {
+        let backoff = BackoffPolicy::new();
+        let mut attempt: u32 = 0;
+
+        loop {
+            let rpcmsg = crate::rpc::compose_subscribe_process_rpcmsg(
+                "",
+                &executortype,
+                WAITING,
+                0,
+                &colonyname,
+                &prvkey,
+            );
+
+            match crate::rpc::send_ws_subscribe_process(rpcmsg).await {
+                Ok(()) => {
+                    attempt = 0;
+                    // A matching state change was observed; claim it the
+                    // same way the polling loop does.
+                    if let Ok(process) = crate::assign(&colonyname, 0, &prvkey).await {
+                        if matches_filter(&process.spec.funcname, &funcname_filter) {
+                            if tx.send(process).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    if !e.conn_err() {
+                        // Not a connection error (e.g. a normal timeout);
+                        // resubscribe immediately.
+                        continue;
+                    }
+                    if !backoff.should_retry(attempt) {
+                        return;
+                    }
+                    crate::rt::sleep(backoff.delay(attempt)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+fn matches_filter(funcname: &str, filter: &str) -> bool {
+    filter.is_empty() || funcname == filter
+}
+
+/// Subscribes to blueprint lifecycle/convergence events
+/// (`Added`/`StatusUpdated`/`Reconciled`/`Removed`) in `colonyname`,
+/// restricted to `kind` (empty matches any), over the server's websocket
+/// subscription mechanism — the same approach [`subscribe_processes`] uses
+/// for process state changes — instead of repeatedly polling
+/// `get_blueprint`/`get_blueprints` to notice a reconcile completed. A
+/// reconciler or a live dashboard can `while let Some(event) =
+/// stream.next().await` instead of spinning. The returned stream never ends
+/// on its own; drop it to stop subscribing. Transient connection errors are
+/// retried using [`BackoffPolicy`], mirroring [`subscribe_processes`].
+pub fn subscribe_blueprint_events(colonyname: &str, kind: &str, prvkey: &str) -> ReceiverStream<BlueprintEvent> {
+    let (tx, rx) = mpsc::channel(64);
+    let colonyname = colonyname.to_owned();
+    let kind = kind.to_owned();
+    let prvkey = prvkey.to_owned();
+
+    crate::rt::spawn(async move {
+        let backoff = BackoffPolicy::new();
+        let mut attempt: u32 = 0;
+
+        loop {
+            if tx.is_closed() {
+                return;
+            }
+
+            let tx_for_cb = tx.clone();
+            let result =
+                crate::subscribe_blueprint_events_stream(&colonyname, &kind, "", 0, &prvkey, move |event| {
+                    tx_for_cb.try_send(event).is_ok()
+                })
+                .await;
+
+            match result {
+                // A normal server-side subscription timeout; resubscribe
+                // right away with no backoff.
+                Ok(_) => attempt = 0,
+                Err(e) => {
+                    if !e.conn_err() || !backoff.should_retry(attempt) {
+                        return;
+                    }
+                    crate::rt::sleep(backoff.delay(attempt)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+/// A single log line produced while a process runs.
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub executorname: String,
+    pub message: String,
+    pub timestamp: i64,
+}
+
+impl From<Log> for LogLine {
+    fn from(log: Log) -> LogLine {
+        LogLine {
+            executorname: log.executorname,
+            message: log.message,
+            timestamp: colony_date_as_millis(&log.timestamp),
+        }
+    }
+}
+
+/// Tails a process's logs by polling `get_logs` with an advancing `since`
+/// cursor, yielding only newly observed lines. Intended as a stopgap until a
+/// dedicated log-subscription RPC exists on the server.
+pub fn subscribe_logs(
+    colonyname: &str,
+    processid: &str,
+    poll_interval: std::time::Duration,
+    prvkey: &str,
+) -> ReceiverStream<LogLine> {
+    let (tx, rx) = mpsc::channel(256);
+    let colonyname = colonyname.to_owned();
+    let processid = processid.to_owned();
+    let prvkey = prvkey.to_owned();
+
+    crate::rt::spawn(async move {
+        let mut since: i64 = 0;
+        loop {
+            if let Ok(logs) = crate::get_logs(&colonyname, &processid, "", 100, since, &prvkey).await {
+                for log in logs {
+                    since = since.max(colony_date_as_millis(&log.timestamp));
+                    if tx.send(log.into()).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            crate::rt::sleep(poll_interval).await;
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+/// Shuts down a [`follow_logs`] poll loop. Cloning is cheap; any clone can
+/// stop the follower.
+#[derive(Debug, Clone)]
+pub struct LogFollowerHandle {
+    stopped: Arc<AtomicBool>,
+}
+
+impl LogFollowerHandle {
+    /// Signals the follower's background task to exit after its current
+    /// poll. The returned stream then ends once any in-flight entries drain.
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Tails `processid`'s logs (optionally narrowed to `executorname`) by
+/// re-polling `get_logs` with `since` advanced past the highest timestamp
+/// seen so far, sleeping `poll_interval` between polls. Returns the stream
+/// of newly observed entries alongside a [`LogFollowerHandle`] for clean
+/// shutdown, since dropping the stream only stops delivery, not the
+/// underlying poll loop.
+pub fn follow_logs(
+    colonyname: &str,
+    processid: &str,
+    executorname: &str,
+    poll_interval: std::time::Duration,
+    prvkey: &str,
+) -> (ReceiverStream<Log>, LogFollowerHandle) {
+    let (tx, rx) = mpsc::channel(256);
+    let colonyname = colonyname.to_owned();
+    let processid = processid.to_owned();
+    let executorname = executorname.to_owned();
+    let prvkey = prvkey.to_owned();
+    let stopped = Arc::new(AtomicBool::new(false));
+    let handle = LogFollowerHandle { stopped: stopped.clone() };
+
+    crate::rt::spawn(async move {
+        let mut since: i64 = 0;
+        while !stopped.load(Ordering::Relaxed) {
+            if let Ok(logs) = crate::get_logs(&colonyname, &processid, &executorname, 100, since, &prvkey).await {
+                for log in logs {
+                    since = since.max(colony_date_as_millis(&log.timestamp));
+                    if tx.send(log).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            crate::rt::sleep(poll_interval).await;
+        }
+    });
+
+    (ReceiverStream::new(rx), handle)
+}
+
+/// Reconnect policy for [`subscribe_channel_resilient`]: `backoff` governs
+/// the delay (and, via `BackoffPolicy::max_retries`, the retry ceiling)
+/// applied after each connection drop, and `max_elapsed`, if set, gives up
+/// once that much wall-clock time has passed since the first connection
+/// attempt, regardless of how many retries remain.
+#[derive(Debug, Clone, Copy)]
+pub struct ReconnectConfig {
+    backoff: BackoffPolicy,
+    max_elapsed: Option<Duration>,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> ReconnectConfig {
+        ReconnectConfig {
+            backoff: crate::backoff::default_policy(),
+            max_elapsed: None,
+        }
+    }
+}
+
+impl ReconnectConfig {
+    pub fn new() -> ReconnectConfig {
+        ReconnectConfig::default()
+    }
+
+    pub fn backoff(mut self, backoff: BackoffPolicy) -> ReconnectConfig {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Bounds total time spent reconnecting; `None` (the default) relies
+    /// solely on `backoff`'s `max_retries`.
+    pub fn max_elapsed(mut self, max_elapsed: Duration) -> ReconnectConfig {
+        self.max_elapsed = Some(max_elapsed);
+        self
+    }
+}
+
+/// Subscribes to `processid`'s state changes the way
+/// [`crate::subscribe_process_stream`] does, but survives a dropped socket
+/// the same way [`subscribe_channel_resilient`] does: on an `RPCError` with
+/// `connection_error = true`, it re-dials with `config.backoff` and
+/// resubscribes, so a transient disconnect doesn't end the stream. A
+/// normal server-side subscription timeout resubscribes immediately with no
+/// backoff; any other error ends the stream.
+pub fn subscribe_process_resilient(
+    processid: &str,
+    executortype: &str,
+    state: i32,
+    timeout: i32,
+    colonyname: &str,
+    prvkey: &str,
+    config: ReconnectConfig,
+    mut on_reconnect: impl FnMut(u32, &RPCError) + Send + 'static,
+) -> ReceiverStream<Process> {
+    let (tx, rx) = mpsc::channel(64);
+    let processid = processid.to_owned();
+    let executortype = executortype.to_owned();
+    let colonyname = colonyname.to_owned();
+    let prvkey = prvkey.to_owned();
+    let started_at = Instant::now();
+
+    crate::rt::spawn(async move {
+        let mut attempt: u32 = 0;
+
+        loop {
+            if tx.is_closed() {
+                return;
+            }
+
+            let tx_for_cb = tx.clone();
+            let result = crate::subscribe_process_stream(
+                &processid,
+                &executortype,
+                state,
+                timeout,
+                &colonyname,
+                &prvkey,
+                move |process| tx_for_cb.try_send(process).is_ok(),
+            )
+            .await;
+
+            match result {
+                // A normal server-side subscription timeout; resubscribe
+                // right away with no backoff.
+                Ok(_) => {
+                    attempt = 0;
+                }
+                Err(e) if e.conn_err() => {
+                    if !config.backoff.should_retry(attempt) {
+                        return;
+                    }
+                    if let Some(max_elapsed) = config.max_elapsed {
+                        if started_at.elapsed() >= max_elapsed {
+                            return;
+                        }
+                    }
+                    on_reconnect(attempt, &e);
+                    crate::rt::sleep(config.backoff.delay(attempt)).await;
+                    attempt += 1;
+                }
+                Err(_) => return,
+            }
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+/// A backpressured subscription to `channelname`'s entries, returned by
+/// [`subscribe_channel_stream`]. Wraps a bounded `mpsc::Receiver` instead of
+/// driving a callback: the background task reserves a slot with
+/// `reserve().await` before delivering each entry, so a slow consumer
+/// blocks the websocket read loop instead of entries piling up
+/// unboundedly, and ordinary `while let Some(entry) = sub.recv().await`
+/// loops compose naturally with other async work.
+pub struct ChannelSubscription {
+    rx: mpsc::Receiver<ChannelEntry>,
+    last_seq: Arc<AtomicI64>,
+}
+
+impl ChannelSubscription {
+    /// Waits for the next entry, or `None` once the subscription has ended
+    /// (e.g. a non-connection RPC error stopped the background task).
+    pub async fn recv(&mut self) -> Option<ChannelEntry> {
+        self.rx.recv().await
+    }
+
+    /// Non-blocking variant of [`ChannelSubscription::recv`].
+    pub fn try_recv(&mut self) -> Result<ChannelEntry, mpsc::error::TryRecvError> {
+        self.rx.try_recv()
+    }
+
+    /// The highest sequence number delivered so far, or the `afterseq` the
+    /// subscription was opened with if nothing has arrived yet. Pass this
+    /// back as `afterseq` to resume after dropping the subscription.
+    pub fn last_seq(&self) -> i64 {
+        self.last_seq.load(Ordering::Relaxed)
+    }
+}
+
+/// Subscribes to `channelname`'s entries the way [`crate::subscribe_channel`]
+/// does, but returns a [`ChannelSubscription`] instead of driving a
+/// callback, so a consumer can `recv().await` it directly instead of
+/// stuffing results into an `Arc<Mutex<Vec<_>>>`. The background task
+/// resubscribes with `afterseq` advanced past the last delivered sequence
+/// each time the server-side long poll elapses with nothing new, and stops
+/// cleanly once the subscription is dropped. It does not itself survive a
+/// dropped socket; pair with the reconnect behavior of
+/// [`subscribe_channel_resilient`] for that.
+pub fn subscribe_channel_stream(
+    processid: &str,
+    channelname: &str,
+    afterseq: i64,
+    timeout: i32,
+    prvkey: &str,
+) -> ChannelSubscription {
+    let (tx, rx) = mpsc::channel(64);
+    let processid = processid.to_owned();
+    let channelname = channelname.to_owned();
+    let prvkey = prvkey.to_owned();
+    let last_seq = Arc::new(AtomicI64::new(afterseq));
+    let last_seq_bg = last_seq.clone();
+
+    crate::rt::spawn(async move {
+        loop {
+            if tx.is_closed() {
+                return;
+            }
+            let since = last_seq_bg.load(Ordering::Relaxed);
+            let rpcmsg = crate::rpc::compose_subscribe_channel_rpcmsg(&processid, &channelname, since, timeout, &prvkey);
+            match crate::rpc::send_ws_subscribe_channel_stream(rpcmsg, timeout, tx.clone(), last_seq_bg.clone()).await {
+                // Server-side long-poll timeout with nothing new;
+                // resubscribe immediately from the advanced cursor.
+                Ok(()) => {}
+                Err(_) => return,
+            }
+        }
+    });
+
+    ChannelSubscription { rx, last_seq }
+}
+
+/// Connection-lifecycle notification passed to
+/// [`subscribe_channel_resilient`]'s `on_event` callback. `attempt`/`error`
+/// on `Disconnected` give the same detail the old `on_reconnect(attempt,
+/// &RPCError)` callback did; `Reconnected` is new: it fires once a
+/// resubscribe after a disconnect actually succeeds, so callers that only
+/// care about "are we currently healthy" don't have to infer it from the
+/// absence of further `Disconnected` events.
+#[derive(Debug)]
+pub enum ChannelReconnectEvent<'a> {
+    /// The websocket dropped; a reconnect attempt (1-based `attempt`) will
+    /// follow after the policy's backoff delay.
+    Disconnected { attempt: u32, error: &'a RPCError },
+    /// A reconnect succeeded after at least one prior `Disconnected` event.
+    Reconnected,
+}
+
+/// Subscribes to `channelname` the way [`crate::subscribe_channel`] does,
+/// but survives a dropped socket: on an `RPCError` with `connection_error
+/// = true`, it re-dials with `config.backoff` and resubscribes with
+/// `afterseq` set to the highest sequence observed so far, so no entry is
+/// missed or redelivered. `on_event` is called with
+/// [`ChannelReconnectEvent::Disconnected`] before each retry sleep and with
+/// [`ChannelReconnectEvent::Reconnected`] once a resubscribe after a
+/// disconnect succeeds, so callers can log/alert on the drop and clear that
+/// alert on recovery instead of just observing retries in isolation.
+/// Non-connection errors (e.g. a malformed request) end the stream rather
+/// than retrying forever against a request that will never succeed.
+pub fn subscribe_channel_resilient(
+    processid: &str,
+    channelname: &str,
+    afterseq: i64,
+    timeout: i32,
+    prvkey: &str,
+    config: ReconnectConfig,
+    mut on_event: impl FnMut(ChannelReconnectEvent) + Send + 'static,
+) -> ReceiverStream<ChannelEntry> {
+    let (tx, rx) = mpsc::channel(256);
+    let processid = processid.to_owned();
+    let channelname = channelname.to_owned();
+    let prvkey = prvkey.to_owned();
+    let cursor = Arc::new(AtomicI64::new(afterseq));
+    let started_at = Instant::now();
+
+    crate::rt::spawn(async move {
+        let mut attempt: u32 = 0;
+        let mut disconnected = false;
+
+        loop {
+            if tx.is_closed() {
+                return;
+            }
+
+            let since = cursor.load(Ordering::Relaxed);
+            let tx_for_cb = tx.clone();
+            let cursor_for_cb = cursor.clone();
+
+            let result = crate::subscribe_channel(&processid, &channelname, since, timeout, &prvkey, move |entries| {
+                for entry in entries {
+                    cursor_for_cb.fetch_max(entry.sequence, Ordering::Relaxed);
+                    if tx_for_cb.try_send(entry).is_err() {
+                        return false;
+                    }
+                }
+                true
+            })
+            .await;
+
+            match result {
+                // A normal server-side subscription timeout; resubscribe
+                // right away with no backoff.
+                Ok(_) => {
+                    attempt = 0;
+                    if disconnected {
+                        on_event(ChannelReconnectEvent::Reconnected);
+                        disconnected = false;
+                    }
+                }
+                Err(e) if e.conn_err() => {
+                    if !config.backoff.should_retry(attempt) {
+                        return;
+                    }
+                    if let Some(max_elapsed) = config.max_elapsed {
+                        if started_at.elapsed() >= max_elapsed {
+                            return;
+                        }
+                    }
+                    on_event(ChannelReconnectEvent::Disconnected { attempt, error: &e });
+                    disconnected = true;
+                    crate::rt::sleep(config.backoff.delay(attempt)).await;
+                    attempt += 1;
+                }
+                Err(_) => return,
+            }
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+/// Subscribes to `channelname`'s entries like [`subscribe_channel_stream`],
+/// but yields `Result<ChannelEntry, RPCError>` items instead of hiding
+/// errors behind a closed channel. The workflow example that motivated this
+/// spawned a background task, reached back into the async world with
+/// `tokio::runtime::Handle::current()` inside the callback, and spun on an
+/// `AtomicBool` to know the subscription was live; a plain
+/// `while let Some(item) = channel_stream(...).next().await` loop replaces
+/// all three, composes with `futures` combinators (`filter`, `take_while`
+/// on `msgtype == "end"`, buffering), and can freely `.await` things like
+/// `channel_append` in its body.
+///
+/// A normal server-side long-poll timeout resubscribes internally from the
+/// advanced cursor and is never surfaced to the caller; any other error is
+/// delivered as one final `Err` item and ends the stream. Unlike
+/// [`subscribe_channel_resilient`], a connection error is not retried here
+/// — pair with that function instead if automatic reconnects are wanted.
+pub fn channel_stream(
+    processid: &str,
+    channelname: &str,
+    afterseq: i64,
+    timeout: i32,
+    prvkey: &str,
+) -> ReceiverStream<Result<ChannelEntry, RPCError>> {
+    let (tx, rx) = mpsc::channel(64);
+    let processid = processid.to_owned();
+    let channelname = channelname.to_owned();
+    let prvkey = prvkey.to_owned();
+    let cursor = Arc::new(AtomicI64::new(afterseq));
+
+    crate::rt::spawn(async move {
+        loop {
+            if tx.is_closed() {
+                return;
+            }
+
+            let since = cursor.load(Ordering::Relaxed);
+            let tx_for_cb = tx.clone();
+            let cursor_for_cb = cursor.clone();
+
+            let result = crate::subscribe_channel(&processid, &channelname, since, timeout, &prvkey, move |entries| {
+                for entry in entries {
+                    cursor_for_cb.fetch_max(entry.sequence, Ordering::Relaxed);
+                    if tx_for_cb.try_send(Ok(entry)).is_err() {
+                        return false;
+                    }
+                }
+                true
+            })
+            .await;
+
+            if let Err(e) = result {
+                let _ = tx.send(Err(e)).await;
+                return;
+            }
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::StreamExt;
+
+    #[test]
+    fn test_matches_filter_empty_matches_everything() {
+        assert!(matches_filter("echo", ""));
+        assert!(matches_filter("anything", ""));
+    }
+
+    #[test]
+    fn test_matches_filter_exact_match_only() {
+        assert!(matches_filter("echo", "echo"));
+        assert!(!matches_filter("echo", "add"));
+    }
+
+    #[tokio::test]
+    async fn test_follow_logs_handle_stops_the_poll_loop() {
+        let (mut rx, handle) = follow_logs("test-colony", "process-123", "", std::time::Duration::from_millis(5), "prvkey");
+        handle.stop();
+        // The background task observes `stopped` and exits; once it does,
+        // the sender is dropped and the stream ends.
+        while rx.next().await.is_some() {}
+    }
+
+    #[test]
+    fn test_reconnect_config_defaults_to_no_max_elapsed() {
+        let config = ReconnectConfig::new();
+        assert!(config.max_elapsed.is_none());
+    }
+
+    #[test]
+    fn test_logline_from_log() {
+        let log = Log {
+            processid: "p1".to_string(),
+            colonyname: "c".to_string(),
+            executorname: "exec".to_string(),
+            message: "hello".to_string(),
+            timestamp: "42".to_string(),
+        };
+        let line: LogLine = log.into();
+        assert_eq!(line.executorname, "exec");
+        assert_eq!(line.message, "hello");
+        assert_eq!(line.timestamp, 42);
+    }
+
+    #[test]
+    fn test_channel_subscription_starts_at_afterseq() {
+        let sub = subscribe_channel_stream("process-123", "chan", 41, 1, "prvkey");
+        assert_eq!(sub.last_seq(), 41);
+    }
+
+    #[test]
+    fn test_channel_reconnect_event_display_fields() {
+        let error = RPCError::new("connection refused", true);
+        let event = ChannelReconnectEvent::Disconnected { attempt: 2, error: &error };
+        match event {
+            ChannelReconnectEvent::Disconnected { attempt, error } => {
+                assert_eq!(attempt, 2);
+                assert!(error.conn_err());
+            }
+            ChannelReconnectEvent::Reconnected => panic!("expected Disconnected"),
+        }
+    }
+}
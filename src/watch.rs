@@ -0,0 +1,299 @@
+//! In-memory change-feed for `ProcessGraph` id-set transitions and
+//! `Executor` state changes.
+//!
+//! `ProcessGraph` partitions process ids into `waitingids`/`runningids`/
+//! `successfulids`/`failedids`, and `Executor.state` changes over time, but
+//! observing those transitions required polling. A [`ChangeFeed`] records
+//! diffs as they're observed and [`watch_range`] streams them to a caller
+//! filtered by `colonyname`/`locationname`, with a `since`-style cursor so
+//! a reconnecting client resumes without missing events.
+
+use crate::core::{Executor, ExecutorState, ProcessGraph};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio_stream::wrappers::ReceiverStream;
+
+/// Which of `ProcessGraph`'s four id sets a process belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessSetKind {
+    Waiting,
+    Running,
+    Successful,
+    Failed,
+}
+
+/// A single observed transition.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StateChange {
+    ProcessMoved {
+        colonyname: String,
+        processgraphid: String,
+        processid: String,
+        from: Option<ProcessSetKind>,
+        to: ProcessSetKind,
+    },
+    ExecutorStateChanged {
+        locationname: String,
+        executorid: String,
+        from: ExecutorState,
+        to: ExecutorState,
+    },
+}
+
+/// Selects which slice of the feed a caller is interested in. `None`
+/// matches everything for that dimension.
+#[derive(Debug, Clone, Default)]
+pub struct Filter {
+    pub colonyname: Option<String>,
+    pub locationname: Option<String>,
+}
+
+impl Filter {
+    fn matches(&self, change: &StateChange) -> bool {
+        match change {
+            StateChange::ProcessMoved { colonyname, .. } => match &self.colonyname {
+                Some(f) => f == colonyname,
+                None => true,
+            },
+            StateChange::ExecutorStateChanged { locationname, .. } => match &self.locationname {
+                Some(f) => f == locationname,
+                None => true,
+            },
+        }
+    }
+}
+
+#[derive(Default)]
+struct FeedState {
+    events: Vec<StateChange>,
+    graph_locations: HashMap<String, HashMap<String, ProcessSetKind>>,
+    executor_states: HashMap<String, ExecutorState>,
+}
+
+/// An append-only, in-memory log of [`StateChange`]s. Cheap to clone
+/// (`Arc`-backed); share one instance between the code that observes
+/// `ProcessGraph`/`Executor` snapshots and the callers watching them.
+#[derive(Clone, Default)]
+pub struct ChangeFeed {
+    state: Arc<Mutex<FeedState>>,
+}
+
+impl ChangeFeed {
+    pub fn new() -> ChangeFeed {
+        ChangeFeed::default()
+    }
+
+    /// Diffs `graph` against the last snapshot recorded for
+    /// `graph.processgraphid` and appends a [`StateChange::ProcessMoved`]
+    /// for every process whose set membership changed.
+    pub async fn record_process_graph(&self, graph: &ProcessGraph) {
+        let mut new_locations = HashMap::new();
+        for (kind, ids) in [
+            (ProcessSetKind::Waiting, &graph.waitingids),
+            (ProcessSetKind::Running, &graph.runningids),
+            (ProcessSetKind::Successful, &graph.successfulids),
+            (ProcessSetKind::Failed, &graph.failedids),
+        ] {
+            for id in ids {
+                new_locations.insert(id.clone(), kind);
+            }
+        }
+
+        let mut state = self.state.lock().await;
+        let old_locations = state
+            .graph_locations
+            .get(&graph.processgraphid)
+            .cloned()
+            .unwrap_or_default();
+
+        for (processid, &to) in &new_locations {
+            let from = old_locations.get(processid).copied();
+            if from != Some(to) {
+                state.events.push(StateChange::ProcessMoved {
+                    colonyname: graph.colonyname.clone(),
+                    processgraphid: graph.processgraphid.clone(),
+                    processid: processid.clone(),
+                    from,
+                    to,
+                });
+            }
+        }
+
+        state.graph_locations.insert(graph.processgraphid.clone(), new_locations);
+    }
+
+    /// Diffs `executor.state` against the last recorded state and appends
+    /// an [`StateChange::ExecutorStateChanged`] if it changed.
+    pub async fn record_executor(&self, executor: &Executor) {
+        let mut state = self.state.lock().await;
+        let from = state.executor_states.get(&executor.executorid).copied();
+
+        if from != Some(executor.state) {
+            state.events.push(StateChange::ExecutorStateChanged {
+                locationname: executor.locationname.clone(),
+                executorid: executor.executorid.clone(),
+                from: from.unwrap_or(executor.state),
+                to: executor.state,
+            });
+            state.executor_states.insert(executor.executorid.clone(), executor.state);
+        }
+    }
+
+    /// Returns events recorded after `cursor` that match `filter`, plus the
+    /// cursor to resume from on the next call.
+    async fn events_since(&self, filter: &Filter, cursor: usize) -> (Vec<StateChange>, usize) {
+        let state = self.state.lock().await;
+        let matched = state
+            .events
+            .iter()
+            .skip(cursor)
+            .filter(|c| filter.matches(c))
+            .cloned()
+            .collect();
+        (matched, state.events.len())
+    }
+}
+
+/// Streams [`StateChange`]s matching `filter` from `feed`, starting after
+/// `cursor`. Polls the feed rather than blocking, so it's safe to call
+/// against a feed still being written to by `record_process_graph`/
+/// `record_executor`.
+pub fn watch_range(feed: ChangeFeed, filter: Filter, cursor: usize) -> ReceiverStream<StateChange> {
+    let (tx, rx) = tokio::sync::mpsc::channel(64);
+
+    tokio::spawn(async move {
+        let mut cursor = cursor;
+        loop {
+            let (events, next_cursor) = feed.events_since(&filter, cursor).await;
+            cursor = next_cursor;
+            for event in events {
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    });
+
+    ReceiverStream::new(rx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(colonyname: &str, running: Vec<&str>, successful: Vec<&str>) -> ProcessGraph {
+        ProcessGraph {
+            processgraphid: "pg-1".to_owned(),
+            colonyname: colonyname.to_owned(),
+            state: crate::core::ProcessState::Waiting,
+            rootprocessids: vec![],
+            processids: vec![],
+            waitingids: vec![],
+            runningids: running.into_iter().map(String::from).collect(),
+            successfulids: successful.into_iter().map(String::from).collect(),
+            failedids: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_process_graph_emits_move_on_first_sighting() {
+        let feed = ChangeFeed::new();
+        feed.record_process_graph(&graph("colony1", vec!["p1"], vec![])).await;
+
+        let (events, cursor) = feed.events_since(&Filter::default(), 0).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(cursor, 1);
+        match &events[0] {
+            StateChange::ProcessMoved { from, to, .. } => {
+                assert_eq!(*from, None);
+                assert_eq!(*to, ProcessSetKind::Running);
+            }
+            _ => panic!("expected ProcessMoved"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_process_graph_emits_move_on_transition() {
+        let feed = ChangeFeed::new();
+        feed.record_process_graph(&graph("colony1", vec!["p1"], vec![])).await;
+        feed.record_process_graph(&graph("colony1", vec![], vec!["p1"])).await;
+
+        let (events, _) = feed.events_since(&Filter::default(), 1).await;
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            StateChange::ProcessMoved { from, to, .. } => {
+                assert_eq!(*from, Some(ProcessSetKind::Running));
+                assert_eq!(*to, ProcessSetKind::Successful);
+            }
+            _ => panic!("expected ProcessMoved"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_process_graph_is_quiet_when_unchanged() {
+        let feed = ChangeFeed::new();
+        feed.record_process_graph(&graph("colony1", vec!["p1"], vec![])).await;
+        feed.record_process_graph(&graph("colony1", vec!["p1"], vec![])).await;
+
+        let (events, _) = feed.events_since(&Filter::default(), 1).await;
+        assert!(events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_colonyname() {
+        let feed = ChangeFeed::new();
+        feed.record_process_graph(&graph("colony1", vec!["p1"], vec![])).await;
+        feed.record_process_graph(&graph("colony2", vec!["p2"], vec![])).await;
+
+        let filter = Filter {
+            colonyname: Some("colony2".to_owned()),
+            locationname: None,
+        };
+        let (events, _) = feed.events_since(&filter, 0).await;
+        assert_eq!(events.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_record_executor_emits_change_only_on_state_transition() {
+        let feed = ChangeFeed::new();
+        let mut exec = Executor::new("worker-1", "exec-1", "docker", "colony1");
+        exec.locationname = "us-west".to_owned();
+        exec.state = ExecutorState::Pending;
+
+        feed.record_executor(&exec).await;
+        feed.record_executor(&exec).await;
+        exec.state = ExecutorState::Approved;
+        feed.record_executor(&exec).await;
+
+        let (events, _) = feed.events_since(&Filter::default(), 0).await;
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            StateChange::ExecutorStateChanged { from, to, .. } => {
+                assert_eq!(*from, ExecutorState::Pending);
+                assert_eq!(*to, ExecutorState::Approved);
+            }
+            _ => panic!("expected ExecutorStateChanged"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_watch_range_streams_matching_events() {
+        use tokio_stream::StreamExt;
+
+        let feed = ChangeFeed::new();
+        feed.record_process_graph(&graph("colony1", vec!["p1"], vec![])).await;
+
+        let mut stream = watch_range(feed.clone(), Filter::default(), 0);
+        let first = tokio::time::timeout(Duration::from_secs(1), stream.next())
+            .await
+            .unwrap()
+            .unwrap();
+        match first {
+            StateChange::ProcessMoved { processid, .. } => assert_eq!(processid, "p1"),
+            _ => panic!("expected ProcessMoved"),
+        }
+    }
+}